@@ -0,0 +1,81 @@
+//Reading a 10k story board, as every page draw does, with and without the CachedDatabase,
+//and changing one story on it, as every edit does.
+//Run with `cargo bench --bench read_db`.
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+//jiracli is a binary crate, so pull in the modules the database needs directly
+#[path = "../src/models.rs"]
+mod models;
+#[path = "../src/workflow.rs"]
+mod workflow;
+#[path = "../src/db/mod.rs"]
+mod db;
+
+use db::{JSONFileDatabase, JiraDatabase};
+use models::{DBState, Epic, Status, Story};
+
+const EPICS: usize = 100;
+const STORIES_PER_EPIC: usize = 100;
+
+fn large_board() -> DBState {
+    let mut db_state = DBState::default();
+    for epic in 0..EPICS {
+        let epic_id = db_state.create_epic(Epic::new(format!("Epic {}", epic), "Some description of the epic".to_owned()));
+        for story in 0..STORIES_PER_EPIC {
+            db_state.create_story(Story::new(format!("Story {}", story), "Some description of the story".to_owned()), epic_id).unwrap();
+        }
+    }
+
+    db_state
+}
+
+fn read_db(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+    let cached = JiraDatabase::new(file_path.clone());
+    cached.database.write_db(&large_board()).unwrap();
+    let uncached = JiraDatabase::with_database(Box::new(JSONFileDatabase { file_path }));
+
+    let mut group = c.benchmark_group("read_db 10k stories");
+    group.sample_size(20);
+    group.bench_function("json", |b| b.iter(|| uncached.read_db().unwrap()));
+    group.bench_function("json cached", |b| b.iter(|| cached.read_db().unwrap()));
+    group.finish();
+}
+
+fn write_db(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let json_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+    let sqlite_path = dir.path().join("db.sqlite").to_str().unwrap().to_owned();
+
+    let json = JiraDatabase::new(json_path);
+    json.database.write_db(&large_board()).unwrap();
+    let sqlite = JiraDatabase::new_sqlite(sqlite_path).unwrap();
+    sqlite.database.write_db(&large_board()).unwrap();
+
+    //Flip the status back and forth so every iteration writes a change
+    let story_id = large_board().last_item_id;
+    let update_story_status = |db: &JiraDatabase, index: u32| {
+        let status = if index.is_multiple_of(2) { Status::InProgress } else { Status::Open };
+        db.update_story_status(story_id, status).unwrap();
+    };
+
+    let mut group = c.benchmark_group("update one story of 10k");
+    group.sample_size(20);
+    group.bench_function("json", |b| {
+        let mut index = 0;
+        b.iter(|| { index += 1; update_story_status(&json, index) })
+    });
+    group.bench_function("sqlite", |b| {
+        let mut index = 0;
+        b.iter(|| { index += 1; update_story_status(&sqlite, index) })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read_db, write_db);
+criterion_main!(benches);
@@ -0,0 +1,450 @@
+use std::io::Write;
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use itertools::Itertools;
+use serde::Deserialize;
+
+use crate::db::{ItemType, JiraDatabase};
+use crate::export;
+use crate::server;
+use crate::config::Config;
+use crate::models::{Epic, Status, Story};
+
+#[derive(Parser, Debug)]
+#[command(name = "jiracli", about = "A tiny jira clone for the terminal.")]
+#[command(long_about = "A tiny jira clone for the terminal.\n\nRuns the interactive board when no subcommand is given.")]
+pub struct Cli {
+    /// Storage backend for the board
+    #[arg(long, global = true, value_enum, default_value_t = Backend::Json)]
+    pub backend: Backend,
+
+    /// Board file to use, created when missing. Its workflow.toml is looked up next to it.
+    #[arg(long, global = true, conflicts_with = "profile")]
+    pub db: Option<String>,
+
+    /// Board from the profiles in ~/.config/jiracli/config.toml
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Use the full screen terminal UI instead of the line based one
+    #[arg(long)]
+    pub tui: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// ./data/db.json
+    Json,
+    /// ./data/db.sqlite
+    Sqlite,
+}
+
+impl Cli {
+    /// Opens the board selected by --db, --profile or the config file.
+    pub fn open_board(&self) -> Result<JiraDatabase> {
+        let config = match Config::path() {
+            Some(config_path) => Config::load(&config_path)?,
+            None => Config::default(),
+        };
+
+        config.board(self.db.as_deref(), self.profile.as_deref(), self.backend)?.open()
+    }
+}
+
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Manage epics
+    #[command(subcommand)]
+    Epic(EpicCommand),
+    /// Manage stories
+    #[command(subcommand)]
+    Story(StoryCommand),
+    /// Search epics and stories, e.g. `jiracli search "login status:inprogress epic:2"`
+    Search {
+        query: String,
+    },
+    /// Check the board for dangling, orphan and duplicate stories and a stale last_item_id
+    Doctor {
+        /// Fix the problems that were found
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Export the board as csv (one row per story) or as a markdown report
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Write to a file instead of stdout
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Create epics and stories from a csv file. Rows are grouped by epic_id, or by epic_name without one. Items get new ids.
+    Import {
+        file: String,
+    },
+    /// Copy an existing json board into the selected (empty) backend
+    Migrate {
+        #[arg(long, default_value = "./data/db.json")]
+        from: String,
+    },
+    /// Serve epics and stories as a JSON API on http://127.0.0.1:<port>
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+}
+
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum EpicCommand {
+    /// List all epics
+    List,
+    /// Create a new epic and print its id
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+    },
+    /// Set the status of an epic
+    Status {
+        epic_id: u32,
+        status: Status,
+    },
+    /// Delete an epic and all of its stories
+    Delete {
+        epic_id: u32,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum StoryCommand {
+    /// List the stories of an epic
+    List {
+        #[arg(long)]
+        epic: u32,
+    },
+    /// Create a new story in an epic and print its id
+    Create {
+        #[arg(long)]
+        epic: u32,
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+    },
+    /// Set the status of a story
+    Status {
+        story_id: u32,
+        status: Status,
+    },
+    /// Move a story into another epic
+    Move {
+        story_id: u32,
+        #[arg(long)]
+        to: u32,
+    },
+    /// Delete a story
+    Delete {
+        story_id: u32,
+    },
+}
+
+pub fn run_command(command: Command, db: &JiraDatabase, out: &mut impl Write) -> Result<()> {
+    match command {
+        Command::Epic(epic_command) => run_epic_command(epic_command, db, out),
+        Command::Story(story_command) => run_story_command(story_command, db, out),
+        Command::Search { query } => {
+            let db_state = db.read_db()?;
+
+            for hit in db.search(&query)? {
+                let (item_type, name, status) = match hit.item_type {
+                    ItemType::Epic => ("epic", &db_state.epics[&hit.id].name, &db_state.epics[&hit.id].status),
+                    ItemType::Story => ("story", &db_state.stories[&hit.id].name, &db_state.stories[&hit.id].status),
+                };
+                writeln!(out, "{}\t{}\t{}\t{}", hit.id, item_type, status, name)?;
+            }
+            Ok(())
+        }
+        Command::Doctor { repair } => {
+            let problems = db.doctor(repair)?;
+
+            for problem in &problems {
+                writeln!(out, "{}", problem)?;
+            }
+
+            if problems.is_empty() {
+                writeln!(out, "No problems found.")?;
+            }
+            else if repair {
+                writeln!(out, "Repaired {} problem(s).", problems.len())?;
+            }
+            else {
+                // Fail so scripts can tell a corrupted board apart from a healthy one
+                return Err(anyhow!("Found {} problem(s). Run `jiracli doctor --repair` to fix them.", problems.len()));
+            }
+            Ok(())
+        }
+        Command::Export { format, output } => {
+            let db_state = db.read_db()?;
+
+            let mut writer: Box<dyn Write + '_> = match &output {
+                Some(output) => Box::new(std::fs::File::create(output).with_context(|| format!("Unable to create export file: {}", output))?),
+                None => Box::new(&mut *out),
+            };
+            match format {
+                ExportFormat::Csv => export::export_csv(&db_state, &mut writer),
+                ExportFormat::Markdown => export::export_markdown(&db_state, &mut writer),
+            }
+        }
+        Command::Import { file } => {
+            let reader = std::fs::File::open(&file).with_context(|| format!("Unable to open import file: {}", file))?;
+            let summary = export::import_csv(db, reader)?;
+            writeln!(out, "Imported {} epics and {} stories.", summary.epics, summary.stories)?;
+            Ok(())
+        }
+        Command::Migrate { from } => {
+            let db_state = db.migrate_from_json(from)?;
+            writeln!(out, "Migrated {} epics and {} stories.", db_state.epics.len(), db_state.stories.len())?;
+            Ok(())
+        }
+        Command::Serve { port } => {
+            writeln!(out, "Listening on http://127.0.0.1:{}", port)?;
+            out.flush()?;
+            server::serve(db, port)
+        }
+    }
+}
+
+fn run_epic_command(command: EpicCommand, db: &JiraDatabase, out: &mut impl Write) -> Result<()> {
+    match command {
+        EpicCommand::List => {
+            let db_state = db.read_db()?;
+
+            for epic_id in db_state.epics.keys().sorted() {
+                let epic = &db_state.epics[epic_id];
+                writeln!(out, "{}\t{}\t{}", epic_id, epic.status, epic.name)?;
+            }
+        }
+        EpicCommand::Create { name, description } => {
+            let epic_id = db.create_epic(Epic::new(name, description))?;
+            writeln!(out, "{}", epic_id)?;
+        }
+        EpicCommand::Status { epic_id, status } => {
+            db.update_epic_status(epic_id, status)?;
+        }
+        EpicCommand::Delete { epic_id } => {
+            db.delete_epic(epic_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_story_command(command: StoryCommand, db: &JiraDatabase, out: &mut impl Write) -> Result<()> {
+    match command {
+        StoryCommand::List { epic } => {
+            let db_state = db.read_db()?;
+            let epic = db_state.epics.get(&epic).ok_or_else(|| anyhow!("Invalid Epic Id."))?;
+
+            for story_id in &epic.stories {
+                let story = db_state.stories.get(story_id).ok_or_else(|| anyhow!("Invalid Story Id in epic: {}", story_id))?;
+                writeln!(out, "{}\t{}\t{}", story_id, story.status, story.name)?;
+            }
+        }
+        StoryCommand::Create { epic, name, description } => {
+            let story_id = db.create_story(Story::new(name, description), epic)?;
+            writeln!(out, "{}", story_id)?;
+        }
+        StoryCommand::Status { story_id, status } => {
+            db.update_story_status(story_id, status)?;
+        }
+        StoryCommand::Move { story_id, to } => {
+            let epic_id = db.read_db()?.epic_of_story(story_id)?;
+            db.move_story(story_id, epic_id, to)?;
+        }
+        StoryCommand::Delete { story_id } => {
+            let epic_id = db.read_db()?.epic_of_story(story_id)?;
+            db.delete_story(epic_id, story_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::MockDB;
+
+    fn run(db: &JiraDatabase, args: &[&str]) -> Result<String> {
+        let cli = Cli::try_parse_from(std::iter::once("jiracli").chain(args.iter().copied()))?;
+        let mut out = Vec::new();
+        run_command(cli.command.unwrap(), db, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn no_subcommand_should_parse_as_interactive() {
+        let cli = Cli::try_parse_from(["jiracli"]).unwrap();
+        assert_eq!(cli.command, None);
+    }
+
+    #[test]
+    fn backend_should_default_to_json() {
+        let cli = Cli::try_parse_from(["jiracli"]).unwrap();
+        assert_eq!(cli.backend, Backend::Json);
+
+        let cli = Cli::try_parse_from(["jiracli", "epic", "list", "--backend", "sqlite"]).unwrap();
+        assert_eq!(cli.backend, Backend::Sqlite);
+    }
+
+    #[test]
+    fn db_and_profile_should_parse() {
+        let cli = Cli::try_parse_from(["jiracli", "epic", "list", "--db", "/tmp/project.json"]).unwrap();
+        assert_eq!(cli.db, Some("/tmp/project.json".to_owned()));
+
+        let cli = Cli::try_parse_from(["jiracli", "--profile", "work"]).unwrap();
+        assert_eq!(cli.profile, Some("work".to_owned()));
+
+        assert!(Cli::try_parse_from(["jiracli", "--profile", "work", "--db", "/tmp/project.json"]).is_err());
+    }
+
+    #[test]
+    fn tui_flag_should_parse() {
+        let cli = Cli::try_parse_from(["jiracli", "--tui"]).unwrap();
+        assert!(cli.tui);
+        assert_eq!(cli.command, None);
+    }
+
+    #[test]
+    fn serve_port_should_default_to_8080() {
+        let cli = Cli::try_parse_from(["jiracli", "serve"]).unwrap();
+        assert_eq!(cli.command, Some(Command::Serve { port: 8080 }));
+
+        let cli = Cli::try_parse_from(["jiracli", "serve", "--port", "3000"]).unwrap();
+        assert_eq!(cli.command, Some(Command::Serve { port: 3000 }));
+    }
+
+    #[test]
+    fn invalid_status_should_fail_to_parse() {
+        let result = Cli::try_parse_from(["jiracli", "story", "status", "7", "done"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn epic_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let output = run(&db, &["epic", "create", "--name", "epic 1", "--description", "first"]).unwrap();
+        assert_eq!(output, "1\n");
+
+        run(&db, &["epic", "status", "1", "in-progress"]).unwrap();
+
+        let output = run(&db, &["epic", "list"]).unwrap();
+        assert_eq!(output, "1\tIN PROGRESS\tepic 1\n");
+
+        run(&db, &["epic", "delete", "1"]).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+    }
+
+    #[test]
+    fn story_commands_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let output = run(&db, &["story", "create", "--epic", "1", "--name", "story 1"]).unwrap();
+        assert_eq!(output, "2\n");
+
+        run(&db, &["story", "status", "2", "resolved"]).unwrap();
+
+        let output = run(&db, &["story", "list", "--epic", "1"]).unwrap();
+        assert_eq!(output, "2\tRESOLVED\tstory 1\n");
+
+        let other_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        run(&db, &["story", "move", "2", "--to", &other_epic_id.to_string()]).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.epics.get(&epic_id).unwrap().stories.is_empty());
+        assert_eq!(db_state.epics.get(&other_epic_id).unwrap().stories, vec![2]);
+
+        run(&db, &["story", "delete", "2"]).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.stories.is_empty());
+        assert!(db_state.epics.get(&other_epic_id).unwrap().stories.is_empty());
+    }
+
+    #[test]
+    fn search_command_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("login".to_owned(), "".to_owned())).unwrap();
+        db.create_story(Story::new("login form".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let output = run(&db, &["search", "login type:story"]).unwrap();
+        assert_eq!(output, "2\tstory\tOPEN\tlogin form\n");
+
+        assert!(run(&db, &["search", "status:unknown"]).is_err());
+    }
+
+    #[test]
+    fn doctor_command_should_report_and_repair() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        assert_eq!(run(&db, &["doctor"]).unwrap(), "No problems found.\n");
+
+        db.transaction(|db_state| {
+            db_state.last_item_id = 0;
+            Ok(())
+        }).unwrap();
+
+        assert!(run(&db, &["doctor"]).is_err());
+
+        let output = run(&db, &["doctor", "--repair"]).unwrap();
+        assert_eq!(output, "last_item_id 0 is lower than the highest id in use 1\nRepaired 1 problem(s).\n");
+        assert_eq!(db.read_db().unwrap().last_item_id, 1);
+    }
+
+    #[test]
+    fn export_and_import_commands_should_work() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("board.csv").to_str().unwrap().to_owned();
+
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let output = run(&db, &["export", "--format", "markdown"]).unwrap();
+        assert!(output.starts_with("# Board"));
+
+        assert_eq!(run(&db, &["export", "--output", &file_path]).unwrap(), "");
+
+        let other_db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let output = run(&other_db, &["import", &file_path]).unwrap();
+        assert_eq!(output, "Imported 1 epics and 1 stories.\n");
+        assert_eq!(other_db.read_db().unwrap().stories.get(&2).unwrap().name, "story");
+
+        assert!(run(&other_db, &["import", "./no/such/file.csv"]).is_err());
+    }
+
+    #[test]
+    fn story_commands_should_error_if_invalid_ids() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        assert!(run(&db, &["story", "list", "--epic", "999"]).is_err());
+        assert!(run(&db, &["story", "create", "--epic", "999", "--name", "story"]).is_err());
+        assert!(run(&db, &["story", "status", "999", "closed"]).is_err());
+        assert!(run(&db, &["story", "move", "999", "--to", "1"]).is_err());
+        assert!(run(&db, &["story", "delete", "999"]).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+use serde::Deserialize;
+
+use crate::cli::Backend;
+use crate::db::JiraDatabase;
+use crate::models::DBState;
+use crate::workflow::Workflow;
+
+/// Named boards to pick from with `--profile`, kept in $XDG_CONFIG_HOME/jiracli/config.toml (~/.config/jiracli/config.toml):
+///
+/// ```toml
+/// # Used when neither --profile nor --db is given
+/// default_profile = "work"
+///
+/// [profiles.work]
+/// db = "~/boards/work.json"
+///
+/// [profiles.home]
+/// db = "~/boards/home.sqlite"
+/// backend = "sqlite"
+/// # Optional, defaults to workflow.toml next to the db
+/// workflow = "~/boards/home-workflow.toml"
+/// ```
+///
+/// Relative paths are relative to the config file.
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+    // Where the config was loaded from, to resolve relative paths
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+struct Profile {
+    db: String,
+    // Overrides --backend
+    backend: Option<Backend>,
+    workflow: Option<String>,
+}
+
+/// The board to open, e.g. one of the profiles.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Board {
+    pub name: String,
+    pub backend: Backend,
+    pub db_path: PathBuf,
+    pub workflow_path: PathBuf,
+    // Boards that were asked for by name or path start out empty
+    create_if_missing: bool,
+}
+
+impl Config {
+    /// The config file location, following the XDG base directory spec.
+    pub fn path() -> Option<PathBuf> {
+        config_path(std::env::var_os("XDG_CONFIG_HOME"), std::env::var_os("HOME"))
+    }
+
+    pub fn parse(config_toml: &str, dir: &Path) -> Result<Self> {
+        let config = toml::from_str::<Config>(config_toml)?;
+        Ok(Config { dir: dir.to_path_buf(), ..config })
+    }
+
+    /// Loads the config file, or an empty config when there is none.
+    pub fn load(file_path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(file_path) {
+            Ok(config_toml) => {
+                let dir = file_path.parent().unwrap_or(Path::new("."));
+                Self::parse(&config_toml, dir).with_context(|| format!("Invalid config file: {}", file_path.display()))
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| format!("Unable to read config file: {}", file_path.display())),
+        }
+    }
+
+    /// Picks the board from `--db`, `--profile` or the default profile, in that order.
+    /// Without any of them it is the board in ./data, as before there were profiles.
+    pub fn board(&self, db: Option<&str>, profile: Option<&str>, backend: Backend) -> Result<Board> {
+        if let Some(db) = db {
+            let db_path = PathBuf::from(db);
+            let name = db_path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| db.to_owned());
+
+            return Ok(Board { name, backend, workflow_path: workflow_next_to(&db_path), db_path, create_if_missing: true });
+        }
+
+        let Some(name) = profile.or(self.default_profile.as_deref()) else {
+            let db_path = match backend {
+                Backend::Json => PathBuf::from("./data/db.json"),
+                Backend::Sqlite => PathBuf::from("./data/db.sqlite"),
+            };
+            return Ok(Board { name: "default".to_owned(), backend, db_path, workflow_path: PathBuf::from("./data/workflow.toml"), create_if_missing: false });
+        };
+
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            anyhow!("Unknown profile '{}'. Configured profiles: {}", name, self.profiles.keys().join(", "))
+        })?;
+
+        let db_path = self.resolve(&profile.db);
+        let workflow_path = profile.workflow.as_deref().map(|workflow| self.resolve(workflow)).unwrap_or_else(|| workflow_next_to(&db_path));
+
+        Ok(Board { name: name.to_owned(), backend: profile.backend.unwrap_or(backend), db_path, workflow_path, create_if_missing: true })
+    }
+
+    // Expands ~ and makes relative paths relative to the config file
+    fn resolve(&self, path: &str) -> PathBuf {
+        match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+            (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+            _ => self.dir.join(path),
+        }
+    }
+}
+
+impl Board {
+    pub fn open(&self) -> Result<JiraDatabase> {
+        let create = self.create_if_missing && !self.db_path.exists();
+        if let Some(dir) = self.db_path.parent().filter(|dir| create && !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Unable to create directory for board: {}", dir.display()))?;
+        }
+
+        let db_path = self.db_path.to_string_lossy().into_owned();
+        let db = match self.backend {
+            Backend::Json => {
+                let db = JiraDatabase::new(db_path);
+                if create {
+                    db.database.write_db(&DBState::default())?;
+                }
+                db
+            }
+            Backend::Sqlite => JiraDatabase::new_sqlite(db_path)?,
+        };
+
+        // Both backends share the workflow, any status change is allowed without one
+        let workflow = Workflow::load(&self.workflow_path.to_string_lossy())?;
+        Ok(db.with_workflow(workflow).with_name(self.name.clone()))
+    }
+}
+
+fn config_path(xdg_config_home: Option<std::ffi::OsString>, home: Option<std::ffi::OsString>) -> Option<PathBuf> {
+    //An empty or relative XDG_CONFIG_HOME is invalid and should be ignored
+    let config_home = xdg_config_home.map(PathBuf::from).filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_home.join("jiracli").join("config.toml"))
+}
+
+fn workflow_next_to(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("workflow.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG_TOML: &str = r#"
+        default_profile = "work"
+
+        [profiles.work]
+        db = "boards/work.json"
+
+        [profiles.home]
+        db = "/boards/home.sqlite"
+        backend = "sqlite"
+        workflow = "/boards/home-workflow.toml"
+    "#;
+
+    #[test]
+    fn config_path_should_follow_xdg() {
+        assert_eq!(config_path(Some("/xdg".into()), Some("/home/me".into())), Some(PathBuf::from("/xdg/jiracli/config.toml")));
+        assert_eq!(config_path(Some("".into()), Some("/home/me".into())), Some(PathBuf::from("/home/me/.config/jiracli/config.toml")));
+        assert_eq!(config_path(None, Some("/home/me".into())), Some(PathBuf::from("/home/me/.config/jiracli/config.toml")));
+        assert_eq!(config_path(None, None), None);
+    }
+
+    #[test]
+    fn board_should_prefer_db_then_profile_then_default_profile() {
+        let config = Config::parse(CONFIG_TOML, Path::new("/config")).unwrap();
+
+        let board = config.board(Some("/tmp/project.json"), None, Backend::Json).unwrap();
+        assert_eq!(board.name, "project");
+        assert_eq!(board.db_path, PathBuf::from("/tmp/project.json"));
+        assert_eq!(board.workflow_path, PathBuf::from("/tmp/workflow.toml"));
+
+        let board = config.board(None, Some("home"), Backend::Json).unwrap();
+        assert_eq!(board.name, "home");
+        assert_eq!(board.backend, Backend::Sqlite);
+        assert_eq!(board.workflow_path, PathBuf::from("/boards/home-workflow.toml"));
+
+        let board = config.board(None, None, Backend::Json).unwrap();
+        assert_eq!(board.name, "work");
+        assert_eq!(board.db_path, PathBuf::from("/config/boards/work.json"));
+        assert_eq!(board.workflow_path, PathBuf::from("/config/boards/workflow.toml"));
+
+        let error = config.board(None, Some("missing"), Backend::Json).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown profile 'missing'. Configured profiles: home, work");
+    }
+
+    #[test]
+    fn board_should_default_to_the_data_directory() {
+        let board = Config::default().board(None, None, Backend::Sqlite).unwrap();
+
+        assert_eq!(board.name, "default");
+        assert_eq!(board.db_path, PathBuf::from("./data/db.sqlite"));
+        assert_eq!(board.workflow_path, PathBuf::from("./data/workflow.toml"));
+        assert!(!board.create_if_missing);
+    }
+
+    #[test]
+    fn load_should_default_when_file_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("config.toml");
+
+        assert_eq!(Config::load(&file_path).unwrap(), Config::default());
+
+        std::fs::write(&file_path, "profiles = 1").unwrap();
+        assert!(Config::load(&file_path).is_err());
+    }
+
+    #[test]
+    fn open_should_create_missing_boards() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("boards").join("project.json");
+
+        let config = Config::default();
+        let db = config.board(Some(db_path.to_str().unwrap()), None, Backend::Json).unwrap().open().unwrap();
+
+        assert_eq!(db.name, "project");
+        assert_eq!(db.read_db().unwrap(), DBState::default());
+        assert!(db_path.exists());
+    }
+}
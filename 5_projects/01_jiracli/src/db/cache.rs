@@ -0,0 +1,173 @@
+use std::cell::RefCell;
+use std::time::SystemTime;
+
+use anyhow::Result;
+
+use crate::db::{Database, DatabaseLock, HistoryEvent};
+use crate::models::DBState;
+
+// Identifies a version of the database file without reading it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+/// Keeps the last read `DBState` so that pages can read the board on every draw without parsing the file again.
+/// The cache is dropped on write and whenever the file's mtime or size changes, e.g. when another jiracli process writes.
+/// Without a file it is only dropped on write. Taking the lock drops it too, so transactions never start from a cached board.
+pub struct CachedDatabase {
+    inner: Box<dyn Database>,
+    cached: RefCell<Option<(Option<FileStamp>, DBState)>>,
+}
+
+impl CachedDatabase {
+    pub fn new(inner: Box<dyn Database>) -> Self {
+        CachedDatabase { inner, cached: RefCell::new(None) }
+    }
+
+    fn file_stamp(&self) -> Option<FileStamp> {
+        let metadata = std::fs::metadata(self.inner.file_path()?).ok()?;
+        Some(FileStamp { modified: metadata.modified().ok()?, len: metadata.len() })
+    }
+}
+
+impl Database for CachedDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        //Stamp before reading, so a write that lands in between makes the next read miss instead of keeping stale data.
+        let stamp = self.file_stamp();
+
+        if let Some((cached_stamp, db_state)) = &*self.cached.borrow() {
+            if *cached_stamp == stamp {
+                return Ok(db_state.clone());
+            }
+        }
+
+        let db_state = self.inner.read_db()?;
+        self.cached.replace(Some((stamp, db_state.clone())));
+
+        Ok(db_state)
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        self.cached.take();
+        self.inner.write_db(db_state)
+    }
+
+    fn write_changes(&self, before: &DBState, db_state: &DBState) -> Result<()> {
+        self.cached.take();
+        self.inner.write_changes(before, db_state)
+    }
+
+    fn append_history(&self, events: &[HistoryEvent]) -> Result<()> {
+        self.inner.append_history(events)
+    }
+
+    fn read_history(&self) -> Result<Vec<HistoryEvent>> {
+        self.inner.read_history()
+    }
+
+    fn lock(&self) -> Result<DatabaseLock> {
+        //Transactions read right after locking. Read the file then, a write of the same size within the mtime resolution looks unchanged.
+        let lock = self.inner.lock()?;
+        self.cached.take();
+
+        Ok(lock)
+    }
+
+    fn file_path(&self) -> Option<&str> {
+        self.inner.file_path()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::db::test_utils::MockDB;
+    use crate::db::{JSONFileDatabase, JiraDatabase};
+    use crate::models::Epic;
+
+    // Counts the reads that reach the wrapped database
+    struct CountingDB {
+        inner: MockDB,
+        reads: Rc<Cell<u32>>,
+    }
+
+    impl Database for CountingDB {
+        fn read_db(&self) -> Result<DBState> {
+            self.reads.set(self.reads.get() + 1);
+            self.inner.read_db()
+        }
+
+        fn write_db(&self, db_state: &DBState) -> Result<()> {
+            self.inner.write_db(db_state)
+        }
+
+        fn append_history(&self, events: &[HistoryEvent]) -> Result<()> {
+            self.inner.append_history(events)
+        }
+
+        fn read_history(&self) -> Result<Vec<HistoryEvent>> {
+            self.inner.read_history()
+        }
+    }
+
+    #[test]
+    fn read_db_should_be_cached_until_write() {
+        let reads = Rc::new(Cell::new(0));
+        let db = CachedDatabase::new(Box::new(CountingDB { inner: MockDB::new(), reads: Rc::clone(&reads) }));
+
+        db.read_db().unwrap();
+        db.read_db().unwrap();
+        assert_eq!(reads.get(), 1);
+
+        let mut db_state = db.read_db().unwrap();
+        db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        db.write_db(&db_state).unwrap();
+
+        assert_eq!(db.read_db().unwrap(), db_state);
+        assert_eq!(reads.get(), 2);
+    }
+
+    #[test]
+    fn read_db_should_notice_changes_by_other_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+        let db = JiraDatabase::new(file_path.clone());
+        db.database.write_db(&DBState::default()).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+
+        //Another process writes to the file directly
+        let other_db = JiraDatabase::with_database(Box::new(JSONFileDatabase { file_path }));
+        other_db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        assert_eq!(db.read_db().unwrap().epics.len(), 1);
+    }
+
+    #[test]
+    fn transaction_should_not_use_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+        let db = JiraDatabase::new(file_path.clone());
+        db.database.write_db(&DBState::default()).unwrap();
+        let epic_id = db.create_epic(Epic::new("aaaa".to_owned(), "".to_owned())).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].name, "aaaa");
+
+        //Another process writes the same number of bytes within the mtime resolution
+        let modified = std::fs::metadata(&file_path).unwrap().modified().unwrap();
+        let json = std::fs::read_to_string(&file_path).unwrap();
+        std::fs::write(&file_path, json.replace("\"aaaa\"", "\"bbbb\"")).unwrap();
+        std::fs::File::options().write(true).open(&file_path).unwrap().set_modified(modified).unwrap();
+
+        db.update_epic_status(epic_id, crate::models::Status::Closed).unwrap();
+
+        let db_state = JSONFileDatabase { file_path }.read_db().unwrap();
+        assert_eq!(db_state.epics[&epic_id].name, "bbbb");
+        assert_eq!(db_state.epics[&epic_id].status, crate::models::Status::Closed);
+    }
+}
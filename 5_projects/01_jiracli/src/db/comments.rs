@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crate::models::{Comment, DBState};
+
+impl DBState {
+    pub fn add_comment(&mut self, comment: Comment) -> Result<u32> {
+        //Check the commented epic or story exists.
+        if !self.epics.contains_key(&comment.item_id) && !self.stories.contains_key(&comment.item_id) {
+            return Err(anyhow!("Invalid Epic or Story Id."));
+        }
+
+        //Replies stay in the thread of the same item.
+        if let Some(reply_to) = comment.reply_to {
+            if self.comments.get(&reply_to).is_none_or(|parent| parent.item_id != comment.item_id) {
+                return Err(anyhow!("Invalid Comment Id to reply to: {}", reply_to));
+            }
+        }
+
+        //Comments share the id counter with epics and stories.
+        let curr_id = self.last_item_id + 1;
+        self.comments.insert(curr_id, comment);
+        self.last_item_id = curr_id;
+
+        Ok(curr_id)
+    }
+
+    /// Deletes a comment of the given item together with all replies to it.
+    pub fn delete_comment(&mut self, item_id: u32, comment_id: u32) -> Result<()> {
+        if self.comments.get(&comment_id).is_none_or(|comment| comment.item_id != item_id) {
+            return Err(anyhow!("Invalid Comment Id."));
+        }
+
+        let mut thread = vec![comment_id];
+        while let Some(id) = thread.pop() {
+            self.comments.remove(&id);
+            thread.extend(self.comments.iter().filter(|(_, comment)| comment.reply_to == Some(id)).map(|(id, _)| *id));
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn delete_comments_of(&mut self, item_id: u32) {
+        self.comments.retain(|_, comment| comment.item_id != item_id);
+    }
+
+    /// The comments of an epic or story in thread order, oldest first, as (depth, comment id, comment).
+    pub fn comments_of(&self, item_id: u32) -> Vec<(usize, u32, &Comment)> {
+        let mut thread = vec![];
+        self.push_replies(item_id, None, 0, &mut thread);
+        thread
+    }
+
+    fn push_replies<'a>(&'a self, item_id: u32, reply_to: Option<u32>, depth: usize, thread: &mut Vec<(usize, u32, &'a Comment)>) {
+        let replies = self.comments.iter()
+            .filter(|(_, comment)| comment.item_id == item_id && comment.reply_to == reply_to)
+            .sorted_by_key(|(id, _)| **id);
+
+        for (id, comment) in replies {
+            thread.push((depth, *id, comment));
+            self.push_replies(item_id, Some(*id), depth + 1, thread);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, Story};
+
+    fn comment(item_id: u32, body: &str, reply_to: Option<u32>) -> Comment {
+        Comment { reply_to, ..Comment::new(item_id, "alice".to_owned(), body.to_owned()) }
+    }
+
+    #[test]
+    fn add_comment_should_validate_item_and_reply() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        let story_id = db_state.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let comment_id = db_state.add_comment(comment(story_id, "first", None)).unwrap();
+        assert_eq!(comment_id, 3);
+        assert!(db_state.add_comment(comment(epic_id, "on the epic", None)).is_ok());
+
+        assert!(db_state.add_comment(comment(999, "", None)).is_err());
+        assert!(db_state.add_comment(comment(story_id, "", Some(999))).is_err());
+        assert!(db_state.add_comment(comment(epic_id, "", Some(comment_id))).is_err());
+    }
+
+    #[test]
+    fn comments_of_should_return_threads_in_order() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+
+        let first = db_state.add_comment(comment(epic_id, "first", None)).unwrap();
+        let second = db_state.add_comment(comment(epic_id, "second", None)).unwrap();
+        let reply = db_state.add_comment(comment(epic_id, "reply", Some(first))).unwrap();
+        let nested = db_state.add_comment(comment(epic_id, "nested", Some(reply))).unwrap();
+
+        let thread = db_state.comments_of(epic_id).into_iter().map(|(depth, id, _)| (depth, id)).collect::<Vec<_>>();
+        assert_eq!(thread, vec![(0, first), (1, reply), (2, nested), (0, second)]);
+    }
+
+    #[test]
+    fn delete_comment_should_delete_replies() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+
+        let first = db_state.add_comment(comment(epic_id, "first", None)).unwrap();
+        let second = db_state.add_comment(comment(epic_id, "second", None)).unwrap();
+        let reply = db_state.add_comment(comment(epic_id, "reply", Some(first))).unwrap();
+        db_state.add_comment(comment(epic_id, "nested", Some(reply))).unwrap();
+
+        assert!(db_state.delete_comment(999, first).is_err());
+        db_state.delete_comment(epic_id, first).unwrap();
+
+        assert_eq!(db_state.comments.keys().collect::<Vec<_>>(), vec![&second]);
+        assert!(db_state.delete_comment(epic_id, first).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crate::models::DBState;
+
+use super::NotFound;
+
+impl DBState {
+    /// Records that `story_id` can't be resolved before `blocker_id`.
+    pub fn add_blocker(&mut self, story_id: u32, blocker_id: u32) -> Result<()> {
+        //Check both story ids are valid.
+        if !self.stories.contains_key(&blocker_id) {
+            return Err(anyhow!("Invalid blocking Story Id."));
+        }
+        let story = self.stories.get(&story_id).ok_or(NotFound::story(story_id))?;
+
+        if story_id == blocker_id {
+            return Err(anyhow!("Story {} can't block itself.", story_id));
+        }
+        if story.blocked_by.contains(&blocker_id) {
+            return Err(anyhow!("Story {} is already blocked by story {}.", story_id, blocker_id));
+        }
+        if self.is_blocked_by(blocker_id, story_id) {
+            return Err(anyhow!("Story {} is already blocked by story {}, directly or indirectly. Links can't form a cycle.", blocker_id, story_id));
+        }
+
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        story.blocked_by.push(blocker_id);
+        story.blocked_by.sort();
+        story.touch();
+
+        Ok(())
+    }
+
+    pub fn remove_blocker(&mut self, story_id: u32, blocker_id: u32) -> Result<()> {
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        if !story.blocked_by.contains(&blocker_id) {
+            return Err(anyhow!("Story {} is not blocked by story {}.", story_id, blocker_id));
+        }
+
+        story.blocked_by.retain(|curr_blocker_id| *curr_blocker_id != blocker_id);
+        story.touch();
+
+        Ok(())
+    }
+
+    // Drops a deleted story from the blockers of every other story
+    pub(super) fn unlink_blocker(&mut self, blocker_id: u32) {
+        for story in self.stories.values_mut().filter(|story| story.blocked_by.contains(&blocker_id)) {
+            story.blocked_by.retain(|curr_blocker_id| *curr_blocker_id != blocker_id);
+        }
+    }
+
+    /// Whether `story_id` waits on `blocker_id`, directly or through other stories.
+    pub fn is_blocked_by(&self, story_id: u32, blocker_id: u32) -> bool {
+        let mut visited = vec![];
+        let mut pending = vec![story_id];
+
+        while let Some(id) = pending.pop() {
+            if visited.contains(&id) {
+                continue;
+            }
+            visited.push(id);
+
+            let blocked_by = self.stories.get(&id).map(|story| story.blocked_by.as_slice()).unwrap_or_default();
+            if blocked_by.contains(&blocker_id) {
+                return true;
+            }
+            pending.extend(blocked_by);
+        }
+
+        false
+    }
+
+    /// The stories that `story_id` blocks, sorted by id.
+    pub fn blocks(&self, story_id: u32) -> Vec<u32> {
+        self.stories.iter()
+            .filter(|(_, story)| story.blocked_by.contains(&story_id))
+            .map(|(id, _)| *id)
+            .sorted()
+            .collect()
+    }
+
+    /// The direct blockers of a story that are neither resolved nor closed.
+    pub fn open_blockers(&self, story_id: u32) -> Vec<u32> {
+        let blocked_by = self.stories.get(&story_id).map(|story| story.blocked_by.as_slice()).unwrap_or_default();
+
+        blocked_by.iter()
+            .filter(|blocker_id| self.stories.get(blocker_id).is_some_and(|blocker| !blocker.status.is_done()))
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, Status, Story};
+
+    fn db_state_with_stories(count: usize) -> (DBState, Vec<u32>) {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        let story_ids = (0..count).map(|_| db_state.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap()).collect();
+
+        (db_state, story_ids)
+    }
+
+    #[test]
+    fn add_blocker_should_validate_links() {
+        let (mut db_state, story_ids) = db_state_with_stories(3);
+        let (first, second, third) = (story_ids[0], story_ids[1], story_ids[2]);
+
+        db_state.add_blocker(third, second).unwrap();
+        db_state.add_blocker(second, first).unwrap();
+        assert_eq!(db_state.stories.get(&third).unwrap().blocked_by, vec![second]);
+        assert_eq!(db_state.blocks(first), vec![second]);
+
+        assert!(db_state.add_blocker(first, first).is_err());
+        assert!(db_state.add_blocker(third, second).is_err());
+        assert!(db_state.add_blocker(first, third).is_err());
+        assert!(db_state.add_blocker(first, 999).is_err());
+        assert!(db_state.add_blocker(999, first).is_err());
+
+        assert!(db_state.is_blocked_by(third, first));
+        assert!(!db_state.is_blocked_by(first, third));
+    }
+
+    #[test]
+    fn remove_blocker_should_work() {
+        let (mut db_state, story_ids) = db_state_with_stories(2);
+
+        db_state.add_blocker(story_ids[1], story_ids[0]).unwrap();
+        db_state.remove_blocker(story_ids[1], story_ids[0]).unwrap();
+
+        assert!(db_state.stories.get(&story_ids[1]).unwrap().blocked_by.is_empty());
+        assert!(db_state.remove_blocker(story_ids[1], story_ids[0]).is_err());
+    }
+
+    #[test]
+    fn open_blockers_should_skip_done_stories() {
+        let (mut db_state, story_ids) = db_state_with_stories(3);
+
+        db_state.add_blocker(story_ids[2], story_ids[0]).unwrap();
+        db_state.add_blocker(story_ids[2], story_ids[1]).unwrap();
+        db_state.update_story_status(story_ids[0], Status::Resolved).unwrap();
+
+        assert_eq!(db_state.open_blockers(story_ids[2]), vec![story_ids[1]]);
+        assert!(db_state.open_blockers(999).is_empty());
+    }
+}
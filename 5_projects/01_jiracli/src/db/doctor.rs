@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::models::{DBState, Epic};
+
+// Name of the epic that orphan stories are attached to on repair
+const ORPHAN_EPIC_NAME: &str = "Orphaned stories";
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Problem {
+    // An epic references a story id that doesn't exist
+    DanglingStory { epic_id: u32, story_id: u32 },
+    // A story that isn't part of any epic
+    OrphanStory { story_id: u32 },
+    // A story listed more than once, within one epic or across epics
+    DuplicateMembership { story_id: u32, epic_ids: Vec<u32> },
+    // A story is blocked by a story id that doesn't exist
+    DanglingBlocker { story_id: u32, blocker_id: u32 },
+    // last_item_id would hand out an id that is already used
+    LastItemIdTooLow { last_item_id: u32, max_id: u32 },
+    // A comment on an epic or story id that doesn't exist
+    OrphanComment { comment_id: u32, item_id: u32 },
+    // A comment answers a comment id that doesn't exist
+    DanglingReply { comment_id: u32, reply_to: u32 },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DanglingStory { epic_id, story_id } => write!(f, "Epic {} references missing story {}", epic_id, story_id),
+            Self::OrphanStory { story_id } => write!(f, "Story {} is not part of any epic", story_id),
+            Self::DuplicateMembership { story_id, epic_ids } => write!(f, "Story {} is listed more than once, in epics {}", story_id, epic_ids.iter().join(", ")),
+            Self::DanglingBlocker { story_id, blocker_id } => write!(f, "Story {} is blocked by missing story {}", story_id, blocker_id),
+            Self::LastItemIdTooLow { last_item_id, max_id } => write!(f, "last_item_id {} is lower than the highest id in use {}", last_item_id, max_id),
+            Self::OrphanComment { comment_id, item_id } => write!(f, "Comment {} is on missing epic or story {}", comment_id, item_id),
+            Self::DanglingReply { comment_id, reply_to } => write!(f, "Comment {} replies to missing comment {}", comment_id, reply_to),
+        }
+    }
+}
+
+impl DBState {
+    /// Scans the db state for corruption. Problems are reported in a stable order.
+    pub fn check_integrity(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+
+        //Comments share the id counter with epics and stories.
+        let max_id = self.epics.keys().chain(self.stories.keys()).chain(self.comments.keys()).copied().max().unwrap_or(0);
+        if self.last_item_id < max_id {
+            problems.push(Problem::LastItemIdTooLow { last_item_id: self.last_item_id, max_id });
+        }
+
+        //Collect every epic a story is listed in, once per listing.
+        let mut memberships: HashMap<u32, Vec<u32>> = HashMap::new();
+        for epic_id in self.epics.keys().sorted() {
+            for story_id in &self.epics[epic_id].stories {
+                if !self.stories.contains_key(story_id) {
+                    problems.push(Problem::DanglingStory { epic_id: *epic_id, story_id: *story_id });
+                }
+                else {
+                    memberships.entry(*story_id).or_default().push(*epic_id);
+                }
+            }
+        }
+
+        for story_id in self.stories.keys().sorted() {
+            match memberships.get(story_id) {
+                None => problems.push(Problem::OrphanStory { story_id: *story_id }),
+                Some(epic_ids) if epic_ids.len() > 1 => problems.push(Problem::DuplicateMembership { story_id: *story_id, epic_ids: epic_ids.clone() }),
+                Some(_) => {}
+            }
+
+            for blocker_id in self.stories[story_id].blocked_by.iter().filter(|blocker_id| !self.stories.contains_key(blocker_id)) {
+                problems.push(Problem::DanglingBlocker { story_id: *story_id, blocker_id: *blocker_id });
+            }
+        }
+
+        for comment_id in self.comments.keys().sorted() {
+            let comment = &self.comments[comment_id];
+            if !self.epics.contains_key(&comment.item_id) && !self.stories.contains_key(&comment.item_id) {
+                problems.push(Problem::OrphanComment { comment_id: *comment_id, item_id: comment.item_id });
+            }
+
+            if let Some(reply_to) = comment.reply_to.filter(|reply_to| !self.comments.contains_key(reply_to)) {
+                problems.push(Problem::DanglingReply { comment_id: *comment_id, reply_to });
+            }
+        }
+
+        problems
+    }
+
+    /// Fixes all problems found by `check_integrity` and returns them.
+    /// Dangling ids and duplicate listings are dropped, keeping the first listing of a story.
+    /// Orphan stories are kept and attached to a new "Orphaned stories" epic.
+    /// Orphan comments are deleted, replies to a missing or deleted comment start a thread of their own.
+    pub fn repair(&mut self) -> Vec<Problem> {
+        let problems = self.check_integrity();
+
+        for problem in &problems {
+            match problem {
+                Problem::LastItemIdTooLow { max_id, .. } => self.last_item_id = *max_id,
+                Problem::DanglingStory { epic_id, story_id } => {
+                    self.epics.entry(*epic_id).and_modify(|epic| epic.stories.retain(|id| id != story_id));
+                }
+                Problem::DuplicateMembership { story_id, epic_ids } => {
+                    //Keep the story in the first epic it is listed in and drop every other listing.
+                    let mut found = false;
+                    for epic_id in epic_ids.iter().unique() {
+                        self.epics.entry(*epic_id).and_modify(|epic| epic.stories.retain(|id| {
+                            let keep = id != story_id || !found;
+                            found |= id == story_id;
+                            keep
+                        }));
+                    }
+                }
+                Problem::DanglingBlocker { story_id, blocker_id } => {
+                    self.stories.entry(*story_id).and_modify(|story| story.blocked_by.retain(|id| id != blocker_id));
+                }
+                Problem::OrphanComment { comment_id, .. } => {
+                    self.comments.remove(comment_id);
+                }
+                Problem::DanglingReply { comment_id, .. } => {
+                    self.comments.entry(*comment_id).and_modify(|comment| comment.reply_to = None);
+                }
+                Problem::OrphanStory { .. } => {}
+            }
+        }
+
+        //Replies to a deleted orphan comment lose their thread too.
+        let comment_ids = self.comments.keys().copied().collect::<Vec<u32>>();
+        for comment in self.comments.values_mut() {
+            comment.reply_to = comment.reply_to.filter(|reply_to| comment_ids.contains(reply_to));
+        }
+
+        //Attach orphan stories last, so the new epic gets a fresh id.
+        let orphan_story_ids = problems.iter()
+            .filter_map(|problem| match problem {
+                Problem::OrphanStory { story_id } => Some(*story_id),
+                _ => None,
+            })
+            .collect::<Vec<u32>>();
+
+        if !orphan_story_ids.is_empty() {
+            let epic_id = self.create_epic(Epic::new(ORPHAN_EPIC_NAME.to_owned(), "Stories recovered by jiracli doctor".to_owned()));
+            self.epics.entry(epic_id).and_modify(|epic| epic.stories = orphan_story_ids);
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Comment, Story};
+
+    fn corrupted_state() -> DBState {
+        let mut db_state = DBState::default();
+
+        let epic_id = db_state.create_epic(Epic::new("epic 1".to_owned(), "".to_owned()));
+        let story_id = db_state.create_story(Story::new("story 2".to_owned(), "".to_owned()), epic_id).unwrap();
+        let other_epic_id = db_state.create_epic(Epic::new("epic 3".to_owned(), "".to_owned()));
+
+        //Story 2 listed twice in epic 1 and once in epic 3, plus a dangling id 99.
+        db_state.epics.get_mut(&epic_id).unwrap().stories.extend([99, story_id]);
+        db_state.epics.get_mut(&other_epic_id).unwrap().stories.push(story_id);
+
+        //Story 4 belongs to no epic, is blocked by a missing story and last_item_id is behind.
+        db_state.stories.insert(4, Story { blocked_by: vec![2, 98], ..Story::new("story 4".to_owned(), "".to_owned()) });
+        db_state.last_item_id = 3;
+
+        db_state
+    }
+
+    #[test]
+    fn check_integrity_should_find_no_problems_in_healthy_state() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        db_state.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        assert!(db_state.check_integrity().is_empty());
+    }
+
+    #[test]
+    fn check_integrity_should_find_all_problems() {
+        let db_state = corrupted_state();
+
+        assert_eq!(db_state.check_integrity(), vec![
+            Problem::LastItemIdTooLow { last_item_id: 3, max_id: 4 },
+            Problem::DanglingStory { epic_id: 1, story_id: 99 },
+            Problem::DuplicateMembership { story_id: 2, epic_ids: vec![1, 1, 3] },
+            Problem::OrphanStory { story_id: 4 },
+            Problem::DanglingBlocker { story_id: 4, blocker_id: 98 },
+        ]);
+    }
+
+    #[test]
+    fn doctor_should_count_comment_ids() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        let comment_id = db_state.add_comment(Comment::new(epic_id, "alice".to_owned(), "first".to_owned())).unwrap();
+        assert!(db_state.check_integrity().is_empty());
+
+        //The comment holds the highest id, so last_item_id may not fall behind it
+        db_state.last_item_id = epic_id;
+        assert_eq!(db_state.check_integrity(), vec![Problem::LastItemIdTooLow { last_item_id: epic_id, max_id: comment_id }]);
+
+        db_state.repair();
+        assert_eq!(db_state.last_item_id, comment_id);
+
+        let next_comment_id = db_state.add_comment(Comment::new(epic_id, "bob".to_owned(), "second".to_owned())).unwrap();
+        assert_ne!(next_comment_id, comment_id);
+        assert_eq!(db_state.comments.get(&comment_id).unwrap().body, "first");
+    }
+
+    #[test]
+    fn repair_should_fix_all_problems() {
+        let mut db_state = corrupted_state();
+
+        let problems = db_state.repair();
+        assert_eq!(problems.len(), 5);
+
+        assert!(db_state.check_integrity().is_empty());
+        assert_eq!(db_state.last_item_id, 5);
+        assert_eq!(db_state.epics.get(&1).unwrap().stories, vec![2]);
+        assert!(db_state.epics.get(&3).unwrap().stories.is_empty());
+
+        let orphan_epic = db_state.epics.get(&5).unwrap();
+        assert_eq!(orphan_epic.name, ORPHAN_EPIC_NAME);
+        assert_eq!(orphan_epic.stories, vec![4]);
+        assert_eq!(db_state.stories.get(&4).unwrap().blocked_by, vec![2]);
+    }
+
+    #[test]
+    fn doctor_should_find_and_repair_broken_comments() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        let comment_id = db_state.add_comment(Comment::new(epic_id, "alice".to_owned(), "first".to_owned())).unwrap();
+
+        //A comment on a deleted story, a reply to a missing comment and a reply to the orphan comment
+        db_state.comments.insert(10, Comment::new(99, "bob".to_owned(), "orphan".to_owned()));
+        db_state.comments.insert(11, Comment { reply_to: Some(98), ..Comment::new(epic_id, "bob".to_owned(), "lost reply".to_owned()) });
+        db_state.comments.insert(12, Comment { reply_to: Some(10), ..Comment::new(epic_id, "carol".to_owned(), "reply to orphan".to_owned()) });
+        db_state.last_item_id = 12;
+
+        assert_eq!(db_state.check_integrity(), vec![
+            Problem::OrphanComment { comment_id: 10, item_id: 99 },
+            Problem::DanglingReply { comment_id: 11, reply_to: 98 },
+        ]);
+
+        db_state.repair();
+        assert!(db_state.check_integrity().is_empty());
+        assert_eq!(db_state.comments.keys().sorted().copied().collect::<Vec<u32>>(), vec![comment_id, 11, 12]);
+        assert_eq!(db_state.comments[&11].reply_to, None);
+        assert_eq!(db_state.comments[&12].reply_to, None);
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::ItemType;
+use crate::models::{Comment, DBState, Epic, Story};
+
+/// One change to an epic or a story, as recorded in the audit log.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct HistoryEvent {
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub item_type: ItemType,
+    pub item_id: u32,
+    pub kind: EventKind,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum EventKind {
+    Created,
+    Deleted,
+    Changed { field: String, from: String, to: String },
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Created => write!(f, "created"),
+            Self::Deleted => write!(f, "deleted"),
+            Self::Changed { field, from, to } => write!(f, "{}: {} -> {}", field, from, to),
+        }
+    }
+}
+
+/// The user changes are attributed to: $JIRACLI_ACTOR, falling back to $USER.
+pub fn current_actor() -> String {
+    std::env::var("JIRACLI_ACTOR")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_owned())
+}
+
+/// Returns the events that turn `before` into `after`: epics, stories and then comments, each sorted by id.
+pub fn events_between(before: &DBState, after: &DBState, actor: &str, timestamp: DateTime<Utc>) -> Vec<HistoryEvent> {
+    let epic_events = item_events(&before.epics, &after.epics, epic_fields)
+        .map(|(item_id, kind)| (ItemType::Epic, item_id, kind));
+    let story_events = item_events(&before.stories, &after.stories, story_fields)
+        .map(|(item_id, kind)| (ItemType::Story, item_id, kind));
+
+    epic_events.chain(story_events).chain(comment_events(before, after))
+        .map(|(item_type, item_id, kind)| HistoryEvent { timestamp, actor: actor.to_owned(), item_type, item_id, kind })
+        .collect()
+}
+
+fn item_events<V>(before: &HashMap<u32, V>, after: &HashMap<u32, V>, fields: fn(&V) -> Vec<(&'static str, String)>) -> impl Iterator<Item = (u32, EventKind)> {
+    let mut events = vec![];
+
+    for id in keys(before, after) {
+        match (before.get(&id), after.get(&id)) {
+            (None, Some(_)) => events.push((id, EventKind::Created)),
+            (Some(_), None) => events.push((id, EventKind::Deleted)),
+            (Some(before), Some(after)) => {
+                for ((field, from), (_, to)) in fields(before).into_iter().zip(fields(after)) {
+                    if from != to {
+                        events.push((id, EventKind::Changed { field: field.to_owned(), from, to }));
+                    }
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    events.into_iter()
+}
+
+// Comments are recorded as changes of the epic or story they are on, e.g. "comment 5: - -> alice: looks good".
+// Comments that come and go with their epic or story are covered by its created or deleted event.
+fn comment_events(before: &DBState, after: &DBState) -> Vec<(ItemType, u32, EventKind)> {
+    let mut events = vec![];
+
+    for comment_id in keys(&before.comments, &after.comments) {
+        let (before_comment, after_comment) = (before.comments.get(&comment_id), after.comments.get(&comment_id));
+        if before_comment == after_comment {
+            continue;
+        }
+
+        let Some(item_id) = before_comment.or(after_comment).map(|comment| comment.item_id) else { continue };
+        let item_type = if before.epics.contains_key(&item_id) && after.epics.contains_key(&item_id) {
+            ItemType::Epic
+        }
+        else if before.stories.contains_key(&item_id) && after.stories.contains_key(&item_id) {
+            ItemType::Story
+        }
+        else {
+            continue;
+        };
+
+        let field = format!("comment {}", comment_id);
+        events.push((item_type, item_id, EventKind::Changed { field, from: comment_text(before_comment), to: comment_text(after_comment) }));
+    }
+
+    events
+}
+
+fn comment_text(comment: Option<&Comment>) -> String {
+    comment.map_or_else(|| "-".to_owned(), |comment| format!("{}: {}", comment.author, comment.body))
+}
+
+fn keys<K: Eq + Hash + Ord + Copy, V>(before: &HashMap<K, V>, after: &HashMap<K, V>) -> Vec<K> {
+    before.keys().chain(after.keys()).copied().unique().sorted().collect()
+}
+
+// The fields worth auditing. Timestamps are left out since every change touches them.
+fn epic_fields(epic: &Epic) -> Vec<(&'static str, String)> {
+    vec![
+        ("name", epic.name.clone()),
+        ("description", epic.description.clone()),
+        ("status", epic.status.to_string()),
+        ("assignee", optional_string(&epic.assignee)),
+        ("priority", epic.priority.to_string()),
+        ("stories", epic.stories.iter().join(", ")),
+        ("labels", epic.labels.join(", ")),
+    ]
+}
+
+fn story_fields(story: &Story) -> Vec<(&'static str, String)> {
+    vec![
+        ("name", story.name.clone()),
+        ("description", story.description.clone()),
+        ("status", story.status.to_string()),
+        ("assignee", optional_string(&story.assignee)),
+        ("priority", story.priority.to_string()),
+        ("story points", optional_string(&story.story_points)),
+        ("labels", story.labels.join(", ")),
+        ("blocked by", story.blocked_by.iter().join(", ")),
+    ]
+}
+
+fn optional_string<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(|| "-".to_owned(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Status;
+
+    #[test]
+    fn events_between_should_record_creates_changes_and_deletes() {
+        let timestamp = Utc::now();
+        let mut before = DBState::default();
+        let epic_id = before.create_epic(Epic::new("epic".to_owned(), "".to_owned()));
+        let story_id = before.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut after = before.clone();
+        after.update_epic_status(epic_id, Status::InProgress).unwrap();
+        after.delete_story(epic_id, story_id).unwrap();
+        let new_story_id = after.create_story(Story::new("new story".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let kinds = events_between(&before, &after, "alice", timestamp).into_iter()
+            .map(|event| (event.item_type, event.item_id, event.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(kinds, vec![
+            (ItemType::Epic, epic_id, EventKind::Changed { field: "status".to_owned(), from: "OPEN".to_owned(), to: "IN PROGRESS".to_owned() }),
+            (ItemType::Epic, epic_id, EventKind::Changed { field: "stories".to_owned(), from: "2".to_owned(), to: "3".to_owned() }),
+            (ItemType::Story, story_id, EventKind::Deleted),
+            (ItemType::Story, new_story_id, EventKind::Created),
+        ]);
+    }
+
+    #[test]
+    fn events_between_should_ignore_timestamps() {
+        let mut before = DBState::default();
+        let epic_id = before.create_epic(Epic::new("epic".to_owned(), "".to_owned()));
+
+        let mut after = before.clone();
+        after.epics.get_mut(&epic_id).unwrap().touch();
+
+        assert!(events_between(&before, &after, "alice", Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn events_between_should_record_comments_on_their_item() {
+        let mut before = DBState::default();
+        let epic_id = before.create_epic(Epic::new("epic".to_owned(), "".to_owned()));
+        let story_id = before.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+        let comment_id = before.add_comment(Comment::new(story_id, "alice".to_owned(), "first".to_owned())).unwrap();
+
+        let mut after = before.clone();
+        after.delete_comment(story_id, comment_id).unwrap();
+        let new_comment_id = after.add_comment(Comment::new(epic_id, "bob".to_owned(), "second".to_owned())).unwrap();
+
+        let kinds = events_between(&before, &after, "alice", Utc::now()).into_iter()
+            .map(|event| (event.item_type, event.item_id, event.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(kinds, vec![
+            (ItemType::Story, story_id, EventKind::Changed { field: format!("comment {}", comment_id), from: "alice: first".to_owned(), to: "-".to_owned() }),
+            (ItemType::Epic, epic_id, EventKind::Changed { field: format!("comment {}", new_comment_id), from: "-".to_owned(), to: "bob: second".to_owned() }),
+        ]);
+
+        //Deleting the story takes its comments along, which its deleted event already covers
+        let mut deleted = before.clone();
+        deleted.delete_story(epic_id, story_id).unwrap();
+        let events = events_between(&before, &deleted, "alice", Utc::now());
+        assert!(events.iter().all(|event| !matches!(&event.kind, EventKind::Changed { field, .. } if field.starts_with("comment"))));
+    }
+}
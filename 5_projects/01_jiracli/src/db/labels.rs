@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+
+use crate::models::DBState;
+
+use super::NotFound;
+
+/// Labels are stored lowercase and may not contain whitespace or commas, e.g. `tech-debt`.
+pub fn normalize_label(label: &str) -> Result<String> {
+    let normalized = label.trim().to_lowercase();
+    if normalized.is_empty() || normalized.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err(anyhow!("Invalid label '{}'. Labels can't be empty or contain spaces or commas.", label));
+    }
+
+    Ok(normalized)
+}
+
+// Keeps the labels sorted and unique
+fn add_label(labels: &mut Vec<String>, label: &str) -> Result<()> {
+    let label = normalize_label(label)?;
+    if let Err(index) = labels.binary_search(&label) {
+        labels.insert(index, label);
+    }
+
+    Ok(())
+}
+
+fn remove_label(labels: &mut Vec<String>, label: &str) -> Result<()> {
+    let label = normalize_label(label)?;
+    let index = labels.iter().position(|curr_label| *curr_label == label).ok_or_else(|| anyhow!("Label '{}' is not set.", label))?;
+    labels.remove(index);
+
+    Ok(())
+}
+
+impl DBState {
+    pub fn add_epic_label(&mut self, epic_id: u32, label: &str) -> Result<()> {
+        let epic = self.epics.get_mut(&epic_id).ok_or(NotFound::epic(epic_id))?;
+        add_label(&mut epic.labels, label)?;
+        epic.touch();
+
+        Ok(())
+    }
+
+    pub fn remove_epic_label(&mut self, epic_id: u32, label: &str) -> Result<()> {
+        let epic = self.epics.get_mut(&epic_id).ok_or(NotFound::epic(epic_id))?;
+        remove_label(&mut epic.labels, label)?;
+        epic.touch();
+
+        Ok(())
+    }
+
+    pub fn add_story_label(&mut self, story_id: u32, label: &str) -> Result<()> {
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        add_label(&mut story.labels, label)?;
+        story.touch();
+
+        Ok(())
+    }
+
+    pub fn remove_story_label(&mut self, story_id: u32, label: &str) -> Result<()> {
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        remove_label(&mut story.labels, label)?;
+        story.touch();
+
+        Ok(())
+    }
+
+    /// Whether the epic, or any of its stories, carries the label.
+    pub fn epic_has_label(&self, epic_id: u32, label: &str) -> bool {
+        let Some(epic) = self.epics.get(&epic_id) else { return false };
+
+        epic.labels.iter().any(|curr_label| curr_label == label)
+            || epic.stories.iter()
+                .filter_map(|story_id| self.stories.get(story_id))
+                .any(|story| story.labels.iter().any(|curr_label| curr_label == label))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, Story};
+
+    #[test]
+    fn normalize_label_should_work() {
+        assert_eq!(normalize_label(" Tech-Debt ").unwrap(), "tech-debt");
+        assert!(normalize_label("").is_err());
+        assert!(normalize_label("tech debt").is_err());
+        assert!(normalize_label("bug,backend").is_err());
+    }
+
+    #[test]
+    fn labels_should_be_added_and_removed() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        let story_id = db_state.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        db_state.add_epic_label(epic_id, "frontend").unwrap();
+        db_state.add_epic_label(epic_id, "Backend").unwrap();
+        db_state.add_epic_label(epic_id, "backend").unwrap();
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().labels, vec!["backend".to_owned(), "frontend".to_owned()]);
+
+        db_state.remove_epic_label(epic_id, "frontend").unwrap();
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().labels, vec!["backend".to_owned()]);
+        assert!(db_state.remove_epic_label(epic_id, "frontend").is_err());
+
+        db_state.add_story_label(story_id, "bug").unwrap();
+        assert_eq!(db_state.stories.get(&story_id).unwrap().labels, vec!["bug".to_owned()]);
+        db_state.remove_story_label(story_id, "bug").unwrap();
+        assert!(db_state.stories.get(&story_id).unwrap().labels.is_empty());
+
+        assert!(db_state.add_epic_label(999, "bug").is_err());
+        assert!(db_state.add_story_label(999, "bug").is_err());
+    }
+
+    #[test]
+    fn epic_has_label_should_include_stories() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        let story_id = db_state.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        db_state.add_epic_label(epic_id, "backend").unwrap();
+        db_state.add_story_label(story_id, "bug").unwrap();
+
+        assert!(db_state.epic_has_label(epic_id, "backend"));
+        assert!(db_state.epic_has_label(epic_id, "bug"));
+        assert!(!db_state.epic_has_label(epic_id, "frontend"));
+        assert!(!db_state.epic_has_label(999, "bug"));
+    }
+}
@@ -0,0 +1,112 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+
+// How long to wait for another jiracli process to finish before giving up.
+const LOCK_RETRIES: u32 = 20;
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+thread_local! {
+    // Lock files held by this thread, so that e.g. an upgrade during a transaction's read doesn't wait on itself.
+    static HELD_LOCKS: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+/// Advisory lock on a database file, released when dropped.
+pub struct DatabaseLock {
+    _file: Option<File>,
+    // Set on the outermost lock of a thread, which releases it
+    held_path: Option<String>,
+}
+
+impl DatabaseLock {
+    /// A lock for databases that do not need one, e.g. the in memory MockDB.
+    pub fn none() -> Self {
+        DatabaseLock { _file: None, held_path: None }
+    }
+
+    /// Takes an exclusive lock on `<file_path>.lock`.
+    /// The db file itself is replaced on every write so it can't hold the lock.
+    /// Acquiring a lock the same thread already holds succeeds right away, the outer lock keeps holding it.
+    pub fn acquire(file_path: &str) -> Result<Self> {
+        let lock_path = format!("{}.lock", file_path);
+        if HELD_LOCKS.with(|held| held.borrow().contains(&lock_path)) {
+            return Ok(Self::none());
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("Unable to open lock file: {}", lock_path))?;
+
+        for _ in 0..LOCK_RETRIES {
+            match file.try_lock() {
+                Ok(()) => {
+                    HELD_LOCKS.with(|held| held.borrow_mut().insert(lock_path.clone()));
+                    return Ok(DatabaseLock { _file: Some(file), held_path: Some(lock_path) });
+                }
+                Err(TryLockError::WouldBlock) => sleep(LOCK_RETRY_DELAY),
+                Err(TryLockError::Error(error)) => {
+                    return Err(error).with_context(|| format!("Unable to lock database file: {}", file_path));
+                }
+            }
+        }
+
+        Err(anyhow!("Database {} is locked by another jiracli process. Please try again.", file_path))
+    }
+}
+
+impl Drop for DatabaseLock {
+    fn drop(&mut self) {
+        if let Some(lock_path) = &self.held_path {
+            HELD_LOCKS.with(|held| held.borrow_mut().remove(lock_path));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acquire_should_fail_while_lock_is_held() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+        let lock = DatabaseLock::acquire(&file_path);
+        assert!(lock.is_ok());
+
+        let other_thread_path = file_path.clone();
+        let result = std::thread::spawn(move || DatabaseLock::acquire(&other_thread_path).map(|_| ())).join().unwrap();
+        assert!(result.is_err());
+        assert!(result.err().unwrap().to_string().contains("locked by another jiracli process"));
+
+        drop(lock);
+        assert!(DatabaseLock::acquire(&file_path).is_ok());
+    }
+
+    #[test]
+    fn acquire_should_be_reentrant_within_a_thread() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+        let other_thread = |file_path: &str| {
+            let file_path = file_path.to_owned();
+            std::thread::spawn(move || DatabaseLock::acquire(&file_path).is_ok()).join().unwrap()
+        };
+
+        let lock = DatabaseLock::acquire(&file_path).unwrap();
+        let nested = DatabaseLock::acquire(&file_path).unwrap();
+
+        //Only the outer lock releases the file
+        drop(nested);
+        assert!(!other_thread(&file_path));
+
+        drop(lock);
+        assert!(other_thread(&file_path));
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::models::SCHEMA_VERSION;
+
+// Each migration upgrades a db.json document by one schema version, starting from version 0.
+type Migration = fn(&mut Value) -> Result<()>;
+
+const MIGRATIONS: &[Migration] = &[
+    v0_to_v1,
+];
+
+/// Returns the schema version of a db.json document. Documents written before versioning are version 0.
+pub fn schema_version(document: &Value) -> Result<u32> {
+    match document.get("schema_version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| anyhow!("Invalid schema_version in db file: {}", version)),
+    }
+}
+
+/// Upgrades a db.json document to the current schema version in place.
+pub fn migrate(document: &mut Value) -> Result<()> {
+    let version = schema_version(document)?;
+    if version > SCHEMA_VERSION {
+        return Err(anyhow!("Db file has schema version {} but this jiracli only supports up to version {}. Please upgrade jiracli.", version, SCHEMA_VERSION));
+    }
+
+    for (from_version, migration) in (0u32..).zip(MIGRATIONS.iter()).skip(version as usize) {
+        migration(document).map_err(|e| e.context(format!("Unable to migrate db file from schema version {}", from_version)))?;
+        document["schema_version"] = Value::from(from_version + 1);
+    }
+
+    Ok(())
+}
+
+// v1: adds schema_version and drops the redundant "id" fields early boards stored inside each epic and story.
+fn v0_to_v1(document: &mut Value) -> Result<()> {
+    for key in ["epics", "stories"] {
+        let items = document
+            .get_mut(key)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| anyhow!("Missing \"{}\" in db file", key))?;
+
+        for item in items.values_mut() {
+            if let Some(item) = item.as_object_mut() {
+                item.remove("id");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn schema_version_should_default_to_zero() {
+        assert_eq!(schema_version(&json!({ "last_item_id": 0 })).unwrap(), 0);
+        assert_eq!(schema_version(&json!({ "schema_version": 1 })).unwrap(), 1);
+        assert!(schema_version(&json!({ "schema_version": "one" })).is_err());
+    }
+
+    #[test]
+    fn migrate_should_upgrade_legacy_document() {
+        let mut document = json!({
+            "last_item_id": 2,
+            "epics": { "1": { "id": 1, "name": "epic", "description": "", "status": "Open", "stories": [2] } },
+            "stories": { "2": { "id": 2, "name": "story", "description": "", "status": "Open" } }
+        });
+
+        assert!(migrate(&mut document).is_ok());
+
+        assert_eq!(document["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(document["epics"]["1"].get("id"), None);
+        assert_eq!(document["stories"]["2"].get("id"), None);
+        assert_eq!(document["epics"]["1"]["stories"], json!([2]));
+    }
+
+    #[test]
+    fn migrate_should_leave_current_document_untouched() {
+        let document = json!({ "schema_version": SCHEMA_VERSION, "last_item_id": 0, "epics": {}, "stories": {} });
+
+        let mut migrated = document.clone();
+        assert!(migrate(&mut migrated).is_ok());
+        assert_eq!(migrated, document);
+    }
+
+    #[test]
+    fn migrate_should_error_for_newer_document() {
+        let mut document = json!({ "schema_version": SCHEMA_VERSION + 1, "last_item_id": 0, "epics": {}, "stories": {} });
+        assert!(migrate(&mut document).is_err());
+    }
+
+    #[test]
+    fn migrate_should_error_for_malformed_document() {
+        let mut document = json!({ "last_item_id": 0 });
+        assert!(migrate(&mut document).is_err());
+    }
+}
@@ -0,0 +1,1542 @@
+#![allow(dead_code)]
+#![allow(unused_variables)]
+
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+
+use anyhow::{Result, Context, anyhow};
+use chrono::Utc;
+use itertools::Itertools;
+use crate::models::{Comment, DBState, Epic, Story, Status, Priority, SCHEMA_VERSION};
+use crate::workflow::Workflow;
+
+mod cache;
+pub use cache::CachedDatabase;
+
+mod comments;
+
+mod dependencies;
+
+mod doctor;
+pub use doctor::Problem;
+
+mod history;
+pub use history::{current_actor, EventKind, HistoryEvent};
+
+mod labels;
+pub use labels::normalize_label;
+
+mod lock;
+pub use lock::DatabaseLock;
+
+mod migrations;
+
+mod progress;
+pub use progress::Progress;
+
+mod search;
+pub use search::{ItemType, SearchHit, SearchQuery};
+
+mod sqlite;
+pub use sqlite::SqliteDatabase;
+
+mod undo;
+pub use undo::{Change, UndoHistory};
+
+mod watch;
+pub use watch::DatabaseWatcher;
+
+pub struct JiraDatabase {
+    pub database: Box<dyn Database>,
+    pub workflow: Workflow,
+    // Shown in the page header to tell boards apart
+    pub name: String,
+    // The board a pending mutation was based on, see `based_on`
+    base: RefCell<Option<DBState>>,
+}
+
+impl JiraDatabase {
+    pub fn new(file_path: String) -> Self {
+        Self::with_database(Box::new(CachedDatabase::new(Box::new(
+            JSONFileDatabase{
+                file_path
+            }
+        ))))
+    }
+
+    pub fn new_sqlite(file_path: String) -> Result<Self> {
+        Ok(Self::with_database(Box::new(CachedDatabase::new(Box::new(SqliteDatabase::new(file_path)?)))))
+    }
+
+    pub fn with_database(database: Box<dyn Database>) -> Self {
+        JiraDatabase{
+            database,
+            workflow: Workflow::default(),
+            name: "default".to_owned(),
+            base: RefCell::new(None),
+        }
+    }
+
+    pub fn with_workflow(mut self, workflow: Workflow) -> Self {
+        self.workflow = workflow;
+        self
+    }
+
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn read_db(&self) -> Result<DBState> {
+        self.database.read_db()
+    }
+
+    /// Watches the database file for changes made outside of this process, if it is backed by one.
+    pub fn watch(&self) -> Result<Option<DatabaseWatcher>> {
+        self.database.file_path().map(DatabaseWatcher::new).transpose()
+    }
+
+    /// Runs `mutation`, refusing to write if the board no longer matches `base` when the first transaction starts.
+    /// E.g. a teammate saved while a prompt that showed the old board was open.
+    pub fn based_on<T>(&self, base: DBState, mutation: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.base.replace(Some(base));
+        let result = mutation();
+        self.base.take();
+
+        result
+    }
+
+    pub fn search(&self, query: &str) -> Result<Vec<SearchHit>> {
+        let search_query = SearchQuery::parse(query)?;
+        Ok(self.database.read_db()?.search(&search_query))
+    }
+
+    /// Checks the board for corruption, and fixes what it finds when `repair` is set.
+    pub fn doctor(&self, repair: bool) -> Result<Vec<Problem>> {
+        if repair {
+            self.transaction(|db_state| Ok(db_state.repair()))
+        }
+        else {
+            Ok(self.database.read_db()?.check_integrity())
+        }
+    }
+
+    pub fn transaction<T>(&self, mutation: impl FnOnce(&mut DBState) -> Result<T>) -> Result<T> {
+        //Hold the lock until the modified db state is written back
+        let _lock = self.database.lock()?;
+
+        //Read in the database from disk
+        let mut db_state = self.database.read_db()?;
+        let db_state_before = db_state.clone();
+
+        //Only the first transaction of a mutation is checked, later ones build on its own write.
+        if self.base.take().is_some_and(|base| base != db_state) {
+            return Err(anyhow!("The board was changed outside of jiracli while you were editing. Nothing was saved, please check the board and try again."));
+        }
+
+        //Apply the mutation. On error nothing is written, which rolls the whole transaction back.
+        let result = mutation(&mut db_state)?;
+
+        //Write the db state to disk and record what changed in the history.
+        self.database.write_changes(&db_state_before, &db_state)?;
+
+        let events = history::events_between(&db_state_before, &db_state, &history::current_actor(), Utc::now());
+        if !events.is_empty() {
+            self.database.append_history(&events)?;
+        }
+
+        //Return result.
+        Ok(result)
+    }
+
+    /// Returns the recorded history of one epic or story, oldest first.
+    pub fn history(&self, item_type: ItemType, item_id: u32) -> Result<Vec<HistoryEvent>> {
+        let events = self.database.read_history()?;
+        Ok(events.into_iter().filter(|event| event.item_type == item_type && event.item_id == item_id).collect())
+    }
+
+    pub fn migrate_from_json(&self, json_file_path: String) -> Result<DBState> {
+        //Read in the json file to migrate, it is only copied so an older file is not upgraded in place
+        let json_state = JSONFileDatabase{ file_path: json_file_path }.read_without_upgrade()?;
+
+        self.transaction(|db_state| {
+            //Only migrate into an empty board so that nothing gets overwritten
+            if !db_state.epics.is_empty() || !db_state.stories.is_empty() {
+                return Err(anyhow!("Target database is not empty. Refusing to migrate."));
+            }
+
+            *db_state = json_state.clone();
+            Ok(json_state)
+        })
+    }
+    
+    pub fn create_epic(&self, epic: Epic) -> Result<u32> {
+        self.transaction(|db_state| Ok(db_state.create_epic(epic)))
+    }
+    
+    pub fn create_story(&self, story: Story, epic_id: u32) -> Result<u32> {
+        self.transaction(|db_state| db_state.create_story(story, epic_id))
+    }
+    
+    pub fn delete_epic(&self, epic_id: u32) -> Result<()> {
+        self.transaction(|db_state| db_state.delete_epic(epic_id))
+    }
+    
+    pub fn delete_story(&self, epic_id: u32, story_id: u32) -> Result<()> {
+        self.transaction(|db_state| db_state.delete_story(epic_id, story_id))
+    }
+    
+    pub fn update_epic(&self, epic_id: u32, name: String, description: String) -> Result<()> {
+        self.transaction(|db_state| db_state.update_epic(epic_id, name, description))
+    }
+
+    pub fn update_story(&self, story_id: u32, name: String, description: String) -> Result<()> {
+        self.transaction(|db_state| db_state.update_story(story_id, name, description))
+    }
+
+    pub fn move_story(&self, story_id: u32, from_epic_id: u32, to_epic_id: u32) -> Result<()> {
+        self.transaction(|db_state| db_state.move_story(story_id, from_epic_id, to_epic_id))
+    }
+
+    pub fn update_epic_status(&self, epic_id: u32, status: Status) -> Result<()> {
+        self.transaction(|db_state| {
+            //Only allow the status changes of the workflow.
+            let epic = db_state.epics.get(&epic_id).ok_or(NotFound::epic(epic_id))?;
+            self.workflow.check_transition(&epic.status, &status)?;
+
+            //Optionally keep epics open until all of their stories are done.
+            if status == Status::Closed && epic.status != Status::Closed && self.workflow.close_epic_requires_resolved_stories {
+                let progress = db_state.epic_progress(epic_id)?;
+                if progress.open() > 0 {
+                    return Err(anyhow!("Epic {} still has {} open stories. Resolve them before closing the epic.", epic_id, progress.open()));
+                }
+            }
+
+            db_state.update_epic_status(epic_id, status)
+        })
+    }
+    
+    pub fn update_story_status(&self, story_id: u32, status: Status) -> Result<()> {
+        self.transaction(|db_state| {
+            //Only allow the status changes of the workflow.
+            let story = db_state.stories.get(&story_id).ok_or(NotFound::story(story_id))?;
+            self.workflow.check_transition(&story.status, &status)?;
+
+            //A story can't be resolved while it still waits on other stories.
+            let open_blockers = db_state.open_blockers(story_id);
+            if status == Status::Resolved && story.status != Status::Resolved && !open_blockers.is_empty() {
+                return Err(anyhow!("Story {} is blocked by open stories: {}. Resolve them first.", story_id, open_blockers.iter().join(", ")));
+            }
+
+            db_state.update_story_status(story_id, status)
+        })
+    }
+
+    pub fn update_epic_assignee(&self, epic_id: u32, assignee: Option<String>) -> Result<()> {
+        self.transaction(|db_state| db_state.update_epic_assignee(epic_id, assignee))
+    }
+
+    pub fn update_epic_priority(&self, epic_id: u32, priority: Priority) -> Result<()> {
+        self.transaction(|db_state| db_state.update_epic_priority(epic_id, priority))
+    }
+
+    pub fn update_story_assignee(&self, story_id: u32, assignee: Option<String>) -> Result<()> {
+        self.transaction(|db_state| db_state.update_story_assignee(story_id, assignee))
+    }
+
+    pub fn update_story_priority(&self, story_id: u32, priority: Priority) -> Result<()> {
+        self.transaction(|db_state| db_state.update_story_priority(story_id, priority))
+    }
+
+    pub fn update_story_points(&self, story_id: u32, story_points: Option<u32>) -> Result<()> {
+        self.transaction(|db_state| db_state.update_story_points(story_id, story_points))
+    }
+
+    pub fn add_epic_label(&self, epic_id: u32, label: &str) -> Result<()> {
+        self.transaction(|db_state| db_state.add_epic_label(epic_id, label))
+    }
+
+    pub fn remove_epic_label(&self, epic_id: u32, label: &str) -> Result<()> {
+        self.transaction(|db_state| db_state.remove_epic_label(epic_id, label))
+    }
+
+    pub fn add_story_label(&self, story_id: u32, label: &str) -> Result<()> {
+        self.transaction(|db_state| db_state.add_story_label(story_id, label))
+    }
+
+    pub fn remove_story_label(&self, story_id: u32, label: &str) -> Result<()> {
+        self.transaction(|db_state| db_state.remove_story_label(story_id, label))
+    }
+
+    pub fn add_blocker(&self, story_id: u32, blocker_id: u32) -> Result<()> {
+        self.transaction(|db_state| db_state.add_blocker(story_id, blocker_id))
+    }
+
+    pub fn remove_blocker(&self, story_id: u32, blocker_id: u32) -> Result<()> {
+        self.transaction(|db_state| db_state.remove_blocker(story_id, blocker_id))
+    }
+
+    pub fn add_comment(&self, comment: Comment) -> Result<u32> {
+        self.transaction(|db_state| db_state.add_comment(comment))
+    }
+
+    pub fn delete_comment(&self, item_id: u32, comment_id: u32) -> Result<()> {
+        self.transaction(|db_state| db_state.delete_comment(item_id, comment_id))
+    }
+}
+
+/// An epic or story id that isn't on the board. Callers tell it apart from other errors with `downcast_ref`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NotFound {
+    pub item_type: ItemType,
+    pub id: u32,
+}
+
+impl NotFound {
+    pub fn epic(id: u32) -> Self {
+        NotFound { item_type: ItemType::Epic, id }
+    }
+
+    pub fn story(id: u32) -> Self {
+        NotFound { item_type: ItemType::Story, id }
+    }
+}
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.item_type {
+            ItemType::Epic => write!(f, "Epic {} not found.", self.id),
+            ItemType::Story => write!(f, "Story {} not found.", self.id),
+        }
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+// Mutations on an in memory db state. Used by JiraDatabase and inside JiraDatabase::transaction().
+impl DBState {
+    pub fn create_epic(&mut self, epic: Epic) -> u32 {
+        //Get the current ID
+        let curr_id = self.last_item_id + 1;
+
+        //Add the new epic to the db
+        self.epics.insert(curr_id, epic);
+        self.last_item_id = curr_id;
+
+        //Return result.
+        curr_id
+    }
+
+    pub fn create_story(&mut self, story: Story, epic_id: u32) -> Result<u32> {
+        //Check if the epic id is valid.
+        if !self.epics.contains_key(&epic_id){
+            return Err(NotFound::epic(epic_id).into());
+        }
+
+        //Get the current ID
+        let curr_id = self.last_item_id + 1;
+
+        //Add the new story to the db
+        self.stories.insert(curr_id, story);
+
+        //Add the new story id to the correct epic.
+        self.epics.entry(epic_id).and_modify(|epic| epic.stories.push(curr_id));
+        self.last_item_id = curr_id;
+
+        //Return result.
+        Ok(curr_id)
+    }
+
+    pub fn delete_epic(&mut self, epic_id: u32) -> Result<()> {
+        //Get the targeted Epic
+        let tgt_epic = self.epics.get(&epic_id).ok_or(NotFound::epic(epic_id))?;
+
+        //Check the story ids are valid. Sanity check
+        if tgt_epic.stories.iter().any(|story_id| !self.stories.contains_key(story_id)) {
+            return Err(anyhow!("Invalid Story Id in epic... Run `jiracli doctor` to check the database before deleting..."));
+        }
+
+        //Delete the stories of the epic
+        for story_id in tgt_epic.stories.clone(){
+            self.stories.remove(&story_id);
+            self.delete_comments_of(story_id);
+            self.unlink_blocker(story_id);
+        }
+
+        //Remove the epic.
+        self.epics.remove(&epic_id);
+        self.delete_comments_of(epic_id);
+
+        //Return result.
+        Ok(())
+    }
+
+    pub fn delete_story(&mut self, epic_id: u32, story_id: u32) -> Result<()> {
+        //Check if the story id is valid.
+        if !self.stories.contains_key(&story_id){
+            return Err(NotFound::story(story_id).into());
+        }
+
+        //Check if the epic id is valid.
+        if !self.epics.contains_key(&epic_id){
+            return Err(NotFound::epic(epic_id).into());
+        }
+
+        //Remove the story from the db
+        self.stories.remove(&story_id);
+        self.delete_comments_of(story_id);
+        self.unlink_blocker(story_id);
+
+        //Remove the story id from the given epic. Assuming the epic contains the story...
+        self.epics.entry(epic_id).and_modify(|epic| {
+            epic.stories.retain(|curr_story_id| *curr_story_id!= story_id);
+        });
+
+        //Return result.
+        Ok(())
+    }
+
+    // Stories are addressed through their epic, so look up which epic owns the story.
+    pub fn epic_of_story(&self, story_id: u32) -> Result<u32> {
+        self.epics.iter()
+            .find(|(_, epic)| epic.stories.contains(&story_id))
+            .map(|(epic_id, _)| *epic_id)
+            .ok_or_else(|| NotFound::story(story_id).into())
+    }
+
+    pub fn update_epic(&mut self, epic_id: u32, name: String, description: String) -> Result<()> {
+        //Modify the name and description of the epic.
+        let epic = self.epics.get_mut(&epic_id).ok_or(NotFound::epic(epic_id))?;
+        epic.name = name;
+        epic.description = description;
+        epic.touch();
+
+        //Return result.
+        Ok(())
+    }
+
+    pub fn update_story(&mut self, story_id: u32, name: String, description: String) -> Result<()> {
+        //Modify the name and description of the story.
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        story.name = name;
+        story.description = description;
+        story.touch();
+
+        //Return result.
+        Ok(())
+    }
+
+    pub fn move_story(&mut self, story_id: u32, from_epic_id: u32, to_epic_id: u32) -> Result<()> {
+        //Check if the story and both epic ids are valid.
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        if !self.epics.contains_key(&to_epic_id){
+            return Err(anyhow!("Invalid target Epic Id."));
+        }
+        let from_epic = self.epics.get_mut(&from_epic_id).ok_or(NotFound::epic(from_epic_id))?;
+
+        //Check the story really is in the source epic and not moved onto itself.
+        if !from_epic.stories.contains(&story_id){
+            return Err(anyhow!("Story {} is not part of epic {}.", story_id, from_epic_id));
+        }
+        if from_epic_id == to_epic_id{
+            return Err(anyhow!("Story {} is already part of epic {}.", story_id, to_epic_id));
+        }
+
+        //Move the story id from one epic to the other.
+        from_epic.stories.retain(|curr_story_id| *curr_story_id != story_id);
+        self.epics.entry(to_epic_id).and_modify(|epic| {
+            if !epic.stories.contains(&story_id){
+                epic.stories.push(story_id);
+            }
+        });
+        story.touch();
+
+        //Return result.
+        Ok(())
+    }
+
+    pub fn update_epic_status(&mut self, epic_id: u32, status: Status) -> Result<()> {
+        //Modify the status of the epic.
+        let epic = self.epics.get_mut(&epic_id).ok_or(NotFound::epic(epic_id))?;
+        epic.status = status;
+        epic.touch();
+
+        //Return result.
+        Ok(())
+    }
+
+    pub fn update_epic_assignee(&mut self, epic_id: u32, assignee: Option<String>) -> Result<()> {
+        let epic = self.epics.get_mut(&epic_id).ok_or(NotFound::epic(epic_id))?;
+        epic.assignee = assignee;
+        epic.touch();
+
+        Ok(())
+    }
+
+    pub fn update_epic_priority(&mut self, epic_id: u32, priority: Priority) -> Result<()> {
+        let epic = self.epics.get_mut(&epic_id).ok_or(NotFound::epic(epic_id))?;
+        epic.priority = priority;
+        epic.touch();
+
+        Ok(())
+    }
+
+    pub fn update_story_status(&mut self, story_id: u32, status: Status) -> Result<()> {
+        //Modify the status of the story.
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        story.status = status;
+        story.touch();
+
+        //Return result.
+        Ok(())
+    }
+
+    pub fn update_story_assignee(&mut self, story_id: u32, assignee: Option<String>) -> Result<()> {
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        story.assignee = assignee;
+        story.touch();
+
+        Ok(())
+    }
+
+    pub fn update_story_priority(&mut self, story_id: u32, priority: Priority) -> Result<()> {
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        story.priority = priority;
+        story.touch();
+
+        Ok(())
+    }
+
+    pub fn update_story_points(&mut self, story_id: u32, story_points: Option<u32>) -> Result<()> {
+        let story = self.stories.get_mut(&story_id).ok_or(NotFound::story(story_id))?;
+        story.story_points = story_points;
+        story.touch();
+
+        Ok(())
+    }
+}
+
+pub trait Database {
+    fn read_db(&self) -> Result<DBState>;
+    fn write_db(&self, db_state: &DBState) -> Result<()>;
+
+    // Writes `db_state`, changed from `before`, which is what the database held when it was read under the lock.
+    // Backends that can update single rows override this to skip writing, or reading, the whole board.
+    fn write_changes(&self, before: &DBState, db_state: &DBState) -> Result<()> {
+        self.write_db(db_state)
+    }
+
+    // The append-only audit log kept beside the board
+    fn append_history(&self, events: &[HistoryEvent]) -> Result<()>;
+    fn read_history(&self) -> Result<Vec<HistoryEvent>>;
+
+    // Locks the database against other processes for a read -> modify -> write cycle.
+    fn lock(&self) -> Result<DatabaseLock> {
+        Ok(DatabaseLock::none())
+    }
+
+    // The file the database is kept in, if any. Used to notice changes made by other processes.
+    fn file_path(&self) -> Option<&str> {
+        None
+    }
+}
+
+pub struct JSONFileDatabase {
+    pub file_path: String
+}
+
+impl Database for JSONFileDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        
+        //load json file into string
+        let db_json = std::fs::read_to_string(&self.file_path)?;
+        let (version, db_state) = Self::parse(&db_json)?;
+
+        //Older files are upgraded to the current schema, see `upgrade`
+        if version < SCHEMA_VERSION {
+            return self.upgrade();
+        }
+
+        Ok(db_state)
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        let db_json = serde_json::to_string_pretty(db_state)?;
+
+        //Write into a temp file next to the db and rename it over the db, so a crash never leaves a half written file.
+        let tmp_path = format!("{}.tmp", self.file_path);
+        let write_tmp_file = || -> std::io::Result<()> {
+            let mut tmp_file = std::fs::File::create(&tmp_path)?;
+            tmp_file.write_all(db_json.as_bytes())?;
+            tmp_file.sync_all()
+        };
+
+        write_tmp_file()
+            .and_then(|_| std::fs::rename(&tmp_path, &self.file_path))
+            .with_context(|| format!("Unable to write json db file into disk: \nPath: {}\n\n{db_json}\n", self.file_path))
+    }
+
+    fn append_history(&self, events: &[HistoryEvent]) -> Result<()> {
+        //One json event per line in <db>.history.jsonl, only ever appended to.
+        let history_path = self.history_path();
+        let append_events = || -> Result<()> {
+            let mut history_file = std::fs::OpenOptions::new().create(true).append(true).open(&history_path)?;
+            for event in events {
+                writeln!(history_file, "{}", serde_json::to_string(event)?)?;
+            }
+            history_file.sync_all()?;
+            Ok(())
+        };
+
+        append_events().with_context(|| format!("Unable to append to history file: {}", history_path))
+    }
+
+    fn read_history(&self) -> Result<Vec<HistoryEvent>> {
+        let history_path = self.history_path();
+        let history_file = match std::fs::File::open(&history_path) {
+            Ok(history_file) => history_file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => return Err(error).with_context(|| format!("Unable to open history file: {}", history_path)),
+        };
+
+        std::io::BufReader::new(history_file).lines()
+            .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<Vec<HistoryEvent>>>()
+            .with_context(|| format!("Unable to read history file: {}", history_path))
+    }
+
+    fn lock(&self) -> Result<DatabaseLock> {
+        DatabaseLock::acquire(&self.file_path)
+    }
+
+    fn file_path(&self) -> Option<&str> {
+        Some(&self.file_path)
+    }
+}
+
+impl JSONFileDatabase {
+    /// Reads the board in the current schema but leaves an older file as it is, e.g. to copy it into another board.
+    pub fn read_without_upgrade(&self) -> Result<DBState> {
+        let db_json = std::fs::read_to_string(&self.file_path)?;
+        Ok(Self::parse(&db_json)?.1)
+    }
+
+    // Parses a db.json document of any supported schema version into the current schema, along with the version it had.
+    fn parse(db_json: &str) -> Result<(u32, DBState)> {
+        let mut document = serde_json::from_str::<serde_json::Value>(db_json).with_context(|| format!("Unable to parse json file: \n{db_json}\n"))?;
+
+        let version = migrations::schema_version(&document)?;
+        migrations::migrate(&mut document)?;
+
+        let db_state = serde_json::from_value::<DBState>(document).with_context(|| format!("Unable to deserialize json file into db: \n{db_json}\n"))?;
+        Ok((version, db_state))
+    }
+
+    // Writes an older file back in the current schema, keeping a backup of the original next to it.
+    // Done under the lock so that it can't overwrite a write by another process.
+    fn upgrade(&self) -> Result<DBState> {
+        let _lock = self.lock()?;
+
+        //Another process may have upgraded or changed the file while we waited for the lock
+        let db_json = std::fs::read_to_string(&self.file_path)?;
+        let (version, db_state) = Self::parse(&db_json)?;
+
+        if version < SCHEMA_VERSION {
+            let backup_path = format!("{}.v{}.bak", self.file_path, version);
+            std::fs::write(&backup_path, &db_json).with_context(|| format!("Unable to write backup before upgrading db file: {}", backup_path))?;
+            self.write_db(&db_state)?;
+        }
+
+        Ok(db_state)
+    }
+
+    fn history_path(&self) -> String {
+        format!("{}.history.jsonl", self.file_path)
+    }
+}
+
+pub mod test_utils {
+    use std::{cell::RefCell, collections::HashMap};
+
+    use super::*;
+    
+    pub struct MockDB {
+        last_written_state: RefCell<DBState>,
+        history: RefCell<Vec<HistoryEvent>>,
+    }
+
+    impl MockDB {
+        pub fn new() -> Self {
+            Self { last_written_state: RefCell::new(DBState::default()), history: RefCell::new(vec![]) }
+        }    
+    }
+
+    impl Database for MockDB {
+        fn read_db(&self) -> Result<DBState> {
+            let state = self.last_written_state.borrow().clone();
+            Ok(state)
+        }
+
+        fn write_db(&self, db_state: &DBState) -> Result<()> {
+            let latest_state = &self.last_written_state;
+            *latest_state.borrow_mut() = db_state.clone();
+            Ok(())
+        }
+
+        fn append_history(&self, events: &[HistoryEvent]) -> Result<()> {
+            self.history.borrow_mut().extend_from_slice(events);
+            Ok(())
+        }
+
+        fn read_history(&self) -> Result<Vec<HistoryEvent>> {
+            Ok(self.history.borrow().clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::test_utils::MockDB;
+
+    #[test]
+    fn transaction_should_commit_all_mutations() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let result = db.transaction(|db_state| {
+            let epic_id = db_state.create_epic(Epic::new("epic".to_owned(), "".to_owned()));
+            for _ in 0..5 {
+                db_state.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)?;
+            }
+            Ok(epic_id)
+        });
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+
+        assert_eq!(db_state.last_item_id, 6);
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().stories, vec![2, 3, 4, 5, 6]);
+        assert_eq!(db_state.stories.len(), 5);
+    }
+
+    #[test]
+    fn transaction_should_roll_back_on_error() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let db_state_before = db.read_db().unwrap();
+
+        let result = db.transaction(|db_state| {
+            db_state.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)?;
+            db_state.update_epic_status(epic_id, Status::Closed)?;
+            db_state.create_story(Story::new("".to_owned(), "".to_owned()), 999)
+        });
+        assert!(result.is_err());
+
+        assert_eq!(db.read_db().unwrap(), db_state_before);
+    }
+
+    #[test]
+    fn doctor_should_only_write_when_repairing() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        db.transaction(|db_state| {
+            db_state.epics.get_mut(&epic_id).unwrap().stories.push(99);
+            Ok(())
+        }).unwrap();
+
+        let result = db.doctor(false);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![Problem::DanglingStory { epic_id, story_id: 99 }]);
+        assert_eq!(db.read_db().unwrap().epics.get(&epic_id).unwrap().stories, vec![99]);
+
+        let result = db.doctor(true);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().len(), 1);
+        assert!(db.read_db().unwrap().epics.get(&epic_id).unwrap().stories.is_empty());
+        assert!(db.doctor(false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn history_should_record_mutations_per_item() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.update_story_status(story_id, Status::Resolved).unwrap();
+        db.delete_story(epic_id, story_id).unwrap();
+
+        let kinds = db.history(ItemType::Story, story_id).unwrap().into_iter().map(|event| event.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![
+            EventKind::Created,
+            EventKind::Changed { field: "status".to_owned(), from: "OPEN".to_owned(), to: "RESOLVED".to_owned() },
+            EventKind::Deleted,
+        ]);
+
+        let events = db.history(ItemType::Epic, epic_id).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].kind, EventKind::Created);
+    }
+
+    #[test]
+    fn history_should_not_record_failed_transactions() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        assert!(db.create_story(Story::new("".to_owned(), "".to_owned()), 999).is_err());
+
+        assert!(db.database.read_history().unwrap().is_empty());
+    }
+
+    #[test]
+    fn based_on_should_refuse_to_write_over_a_changed_board() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let base = db.read_db().unwrap();
+        db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let result = db.based_on(base, || db.create_epic(Epic::new("".to_owned(), "".to_owned())));
+        assert!(result.is_err());
+        assert_eq!(db.read_db().unwrap().epics.len(), 1);
+
+        //Only the first transaction is checked
+        let base = db.read_db().unwrap();
+        let result = db.based_on(base, || {
+            db.create_epic(Epic::new("".to_owned(), "".to_owned()))?;
+            db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+        });
+        assert!(result.is_ok());
+
+        //And the base is cleared afterwards
+        assert!(db.create_epic(Epic::new("".to_owned(), "".to_owned())).is_ok());
+        assert_eq!(db.read_db().unwrap().epics.len(), 4);
+    }
+
+    #[test]
+    fn update_status_should_follow_workflow() {
+        let workflow = Workflow::parse("statuses = [\"Open\", \"InProgress\", \"Closed\"]\n[transitions]\nOpen = [\"InProgress\"]\nInProgress = [\"Closed\"]").unwrap();
+        let db = JiraDatabase::with_database(Box::new(MockDB::new())).with_workflow(workflow);
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        assert!(db.update_epic_status(epic_id, Status::Closed).is_err());
+        assert!(db.update_epic_status(epic_id, Status::InProgress).is_ok());
+        assert!(db.update_epic_status(epic_id, Status::Closed).is_ok());
+        assert!(db.update_epic_status(epic_id, Status::Open).is_err());
+
+        assert!(db.update_story_status(story_id, Status::Resolved).is_err());
+        assert!(db.update_story_status(story_id, Status::Open).is_ok());
+        assert_eq!(db.read_db().unwrap().stories.get(&story_id).unwrap().status, Status::Open);
+    }
+
+    #[test]
+    fn update_epic_status_should_refuse_to_close_with_open_stories() {
+        let mut workflow = Workflow::default();
+        workflow.close_epic_requires_resolved_stories = true;
+        let db = JiraDatabase::with_database(Box::new(MockDB::new())).with_workflow(workflow);
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let result = db.update_epic_status(epic_id, Status::Closed);
+        assert!(result.is_err());
+        assert_eq!(result.err().unwrap().to_string(), format!("Epic {} still has 1 open stories. Resolve them before closing the epic.", epic_id));
+
+        assert!(db.update_epic_status(epic_id, Status::Resolved).is_ok());
+
+        db.update_story_status(story_id, Status::Resolved).unwrap();
+        assert!(db.update_epic_status(epic_id, Status::Closed).is_ok());
+    }
+
+    #[test]
+    fn search_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.update_story_status(story_id, Status::InProgress).unwrap();
+
+        let result = db.search("story status:inprogress");
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![SearchHit { item_type: ItemType::Story, id: story_id, epic_id }]);
+
+        assert!(db.search("status:nope").is_err());
+    }
+
+    #[test]
+    fn create_epic_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic = Epic::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic.clone());
+        
+        assert!(result.is_ok());
+
+        let id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+
+        let expected_id = 1;
+
+        assert_eq!(id, expected_id);
+        assert_eq!(db_state.last_item_id, expected_id);
+        assert_eq!(db_state.epics.get(&id), Some(&epic));
+    }
+
+    #[test]
+    fn create_story_should_error_if_invalid_epic_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let non_existent_epic_id = 999;
+
+        let result = db.create_story(story, non_existent_epic_id);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn create_story_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story.clone(), epic_id);
+        assert!(result.is_ok());
+
+        let id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+
+        let expected_id = 2;
+
+        assert_eq!(id, expected_id);
+        assert_eq!(db_state.last_item_id, expected_id);
+        assert!(db_state.epics.get(&epic_id).unwrap().stories.contains(&id));
+        assert_eq!(db_state.stories.get(&id), Some(&story));
+    }
+
+    #[test]
+    fn delete_epic_should_error_if_invalid_epic_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let non_existent_epic_id = 999;
+
+        let result = db.delete_epic(non_existent_epic_id);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn delete_epic_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+
+        let story_id = result.unwrap();
+
+        let result = db.delete_epic(epic_id);
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+
+        let expected_last_id = 2;
+
+        assert_eq!(db_state.last_item_id, expected_last_id);
+        assert_eq!(db_state.epics.get(&epic_id), None);
+        assert_eq!(db_state.stories.get(&story_id), None);
+    }
+
+    #[test]
+    fn update_story_status_should_refuse_to_resolve_blocked_story() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let blocker_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.add_blocker(story_id, blocker_id).unwrap();
+
+        let result = db.update_story_status(story_id, Status::Resolved);
+        assert!(result.is_err());
+        assert_eq!(result.err().unwrap().to_string(), format!("Story {} is blocked by open stories: {}. Resolve them first.", story_id, blocker_id));
+
+        assert!(db.update_story_status(story_id, Status::InProgress).is_ok());
+
+        db.update_story_status(blocker_id, Status::Closed).unwrap();
+        assert!(db.update_story_status(story_id, Status::Resolved).is_ok());
+    }
+
+    #[test]
+    fn delete_story_should_remove_blocker_links() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let blocker_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.add_blocker(story_id, blocker_id).unwrap();
+
+        db.delete_story(epic_id, blocker_id).unwrap();
+
+        assert!(db.read_db().unwrap().stories.get(&story_id).unwrap().blocked_by.is_empty());
+    }
+
+    #[test]
+    fn delete_should_remove_comments() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let other_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let comment_id = db.add_comment(Comment::new(story_id, "alice".to_owned(), "first".to_owned())).unwrap();
+        db.add_comment(Comment::new(other_story_id, "alice".to_owned(), "".to_owned())).unwrap();
+        db.add_comment(Comment::new(epic_id, "alice".to_owned(), "".to_owned())).unwrap();
+        assert_eq!(db.read_db().unwrap().comments.get(&comment_id).unwrap().body, "first");
+
+        db.delete_story(epic_id, story_id).unwrap();
+        assert_eq!(db.read_db().unwrap().comments.len(), 2);
+
+        db.delete_epic(epic_id).unwrap();
+        assert!(db.read_db().unwrap().comments.is_empty());
+    }
+
+    #[test]
+    fn delete_story_should_error_if_invalid_epic_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+        
+        let story_id = result.unwrap();
+
+        let non_existent_epic_id = 999;
+        
+        let result = db.delete_story(non_existent_epic_id, story_id);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn delete_story_should_error_if_story_not_found_in_epic() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+
+        let non_existent_story_id = 999;
+        
+        let result = db.delete_story(epic_id, non_existent_story_id);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn delete_story_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+
+        let story_id = result.unwrap();
+
+        let result = db.delete_story(epic_id, story_id);
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+
+        let expected_last_id = 2;
+
+        assert_eq!(db_state.last_item_id, expected_last_id);
+        assert!(!db_state.epics.get(&epic_id).unwrap().stories.contains(&story_id));
+        assert_eq!(db_state.stories.get(&story_id), None);
+    }
+
+    #[test]
+    fn update_epic_should_error_if_invalid_epic_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let non_existent_epic_id = 999;
+
+        let result = db.update_epic(non_existent_epic_id, "".to_owned(), "".to_owned());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn update_epic_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("name".to_owned(), "description".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let result = db.update_epic(epic_id, "new name".to_owned(), "new description".to_owned());
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+        let epic = db_state.epics.get(&epic_id).unwrap();
+
+        assert_eq!(epic.name, "new name".to_owned());
+        assert_eq!(epic.description, "new description".to_owned());
+        assert_eq!(epic.stories, vec![story_id]);
+    }
+
+    #[test]
+    fn update_story_should_error_if_invalid_story_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let non_existent_story_id = 999;
+
+        let result = db.update_story(non_existent_story_id, "".to_owned(), "".to_owned());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn update_story_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("name".to_owned(), "description".to_owned()), epic_id).unwrap();
+        db.update_story_status(story_id, Status::InProgress).unwrap();
+
+        let result = db.update_story(story_id, "new name".to_owned(), "new description".to_owned());
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+        let story = db_state.stories.get(&story_id).unwrap();
+
+        assert_eq!(story.name, "new name".to_owned());
+        assert_eq!(story.description, "new description".to_owned());
+        assert_eq!(story.status, Status::InProgress);
+    }
+
+    #[test]
+    fn move_story_should_error_if_invalid_ids() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let from_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let to_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), from_epic_id).unwrap();
+        let db_state_before = db.read_db().unwrap();
+
+        assert!(db.move_story(999, from_epic_id, to_epic_id).is_err());
+        assert!(db.move_story(story_id, 999, to_epic_id).is_err());
+        assert!(db.move_story(story_id, from_epic_id, 999).is_err());
+        assert!(db.move_story(story_id, to_epic_id, from_epic_id).is_err());
+        assert!(db.move_story(story_id, from_epic_id, from_epic_id).is_err());
+
+        assert_eq!(db.read_db().unwrap(), db_state_before);
+    }
+
+    #[test]
+    fn move_story_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let from_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let to_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), from_epic_id).unwrap();
+        let other_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), to_epic_id).unwrap();
+
+        let result = db.move_story(story_id, from_epic_id, to_epic_id);
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+
+        assert!(db_state.epics.get(&from_epic_id).unwrap().stories.is_empty());
+        assert_eq!(db_state.epics.get(&to_epic_id).unwrap().stories, vec![other_story_id, story_id]);
+        assert!(db_state.stories.contains_key(&story_id));
+    }
+
+    #[test]
+    fn update_epic_status_should_error_if_invalid_epic_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let non_existent_epic_id = 999;
+
+        let result = db.update_epic_status(non_existent_epic_id, Status::Closed);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn update_epic_status_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic = Epic::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.update_epic_status(epic_id, Status::Closed);
+
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().status, Status::Closed);
+    }
+
+    #[test]
+    fn update_epic_assignee_and_priority_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let created_at = db.read_db().unwrap().epics.get(&epic_id).unwrap().created_at;
+
+        assert!(db.update_epic_assignee(epic_id, Some("alice".to_owned())).is_ok());
+        assert!(db.update_epic_priority(epic_id, Priority::High).is_ok());
+        assert!(db.update_epic_assignee(999, None).is_err());
+        assert!(db.update_epic_priority(999, Priority::Low).is_err());
+
+        let db_state = db.read_db().unwrap();
+        let epic = db_state.epics.get(&epic_id).unwrap();
+
+        assert_eq!(epic.assignee, Some("alice".to_owned()));
+        assert_eq!(epic.priority, Priority::High);
+        assert_eq!(epic.created_at, created_at);
+        assert!(epic.updated_at >= created_at);
+    }
+
+    #[test]
+    fn update_story_fields_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        assert!(db.update_story_assignee(story_id, Some("bob".to_owned())).is_ok());
+        assert!(db.update_story_priority(story_id, Priority::Critical).is_ok());
+        assert!(db.update_story_points(story_id, Some(5)).is_ok());
+        assert!(db.update_story_assignee(999, None).is_err());
+        assert!(db.update_story_priority(999, Priority::Low).is_err());
+        assert!(db.update_story_points(999, Some(1)).is_err());
+
+        let db_state = db.read_db().unwrap();
+        let story = db_state.stories.get(&story_id).unwrap();
+
+        assert_eq!(story.assignee, Some("bob".to_owned()));
+        assert_eq!(story.priority, Priority::Critical);
+        assert_eq!(story.story_points, Some(5));
+
+        assert!(db.update_story_assignee(story_id, None).is_ok());
+        assert_eq!(db.read_db().unwrap().stories.get(&story_id).unwrap().assignee, None);
+    }
+
+    #[test]
+    fn update_story_status_should_error_if_invalid_story_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let non_existent_story_id = 999;
+
+        let result = db.update_story_status(non_existent_story_id, Status::Closed);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn update_story_status_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+
+        let story_id = result.unwrap();
+
+        let result = db.update_story_status(story_id, Status::Closed);
+
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+
+        assert_eq!(db_state.stories.get(&story_id).unwrap().status, Status::Closed);
+    }
+
+    #[test]
+    fn migrate_from_json_should_copy_the_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+        let file_contents = r#"{ "last_item_id": 2, "epics": { "1": { "name": "epic 1", "description": "", "status": "Open", "stories": [2] } }, "stories": { "2": { "name": "story 2", "description": "", "status": "Closed" } } }"#;
+        std::fs::write(&file_path, file_contents).unwrap();
+
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        let result = db.migrate_from_json(file_path.clone());
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state, result.unwrap());
+        assert_eq!(db_state.last_item_id, 2);
+        assert_eq!(db_state.epics.get(&1).unwrap().stories, vec![2]);
+        assert_eq!(db_state.stories.get(&2).unwrap().status, Status::Closed);
+
+        //The source board predates schema versioning but is only copied, not upgraded
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), file_contents);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn migrate_from_json_should_error_if_target_not_empty() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+        std::fs::write(&file_path, r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#).unwrap();
+
+        let result = db.migrate_from_json(file_path);
+        assert!(result.is_err());
+    }
+
+    mod database {
+        use std::collections::HashMap;
+        use std::fs::{remove_file};
+        use std::io::Write;
+
+        use super::*;
+
+        #[test]
+        fn read_db_should_fail_with_invalid_path() {
+            let db = JSONFileDatabase { file_path: "INVALID_PATH".to_owned() };
+            assert!(db.read_db().is_err());
+        }
+
+        #[test]
+        fn read_db_should_fail_with_invalid_json() {
+            let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+
+            let file_contents = r#"{ "last_item_id": 0 epics: {} stories {} }"#;
+            write!(tmpfile, "{}", file_contents).unwrap();
+
+            let file_path = "./data/read_db_should_fail_with_invalid_json.json".to_owned();
+
+            let path = tmpfile.into_temp_path();
+            path.persist(&file_path).unwrap();
+
+            let db = JSONFileDatabase { file_path: file_path.clone() };
+
+            let result = db.read_db();
+
+            remove_file(file_path).unwrap();
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn read_db_should_parse_json_file() {
+            let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+
+            let file_contents = r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#;
+            write!(tmpfile, "{}", file_contents).unwrap();
+
+            let file_path = "./data/read_db_should_parse_json_file.json".to_owned();
+
+            let path = tmpfile.into_temp_path();
+            path.persist(&file_path).unwrap();
+
+            let db = JSONFileDatabase { file_path: file_path.clone() };
+
+            let result = db.read_db();
+
+            // The file predates schema versioning so reading it also leaves a backup and the lock file of the upgrade behind
+            remove_file(format!("{}.v0.bak", file_path)).unwrap();
+            remove_file(format!("{}.lock", file_path)).unwrap();
+            remove_file(file_path).unwrap();
+
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn read_db_should_default_fields_missing_from_older_files() {
+            let dir = tempfile::tempdir().unwrap();
+            let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+            let file_contents = r#"{ "last_item_id": 2, "epics": { "1": { "name": "epic 1", "description": "", "status": "Open", "stories": [2] } }, "stories": { "2": { "name": "story 2", "description": "", "status": "Closed" } } }"#;
+            std::fs::write(&file_path, file_contents).unwrap();
+
+            let db = JSONFileDatabase { file_path };
+            let db_state = db.read_db().unwrap();
+
+            let epic = db_state.epics.get(&1).unwrap();
+            assert_eq!(epic.assignee, None);
+            assert_eq!(epic.priority, Priority::Medium);
+            assert_eq!(epic.created_at, None);
+
+            let story = db_state.stories.get(&2).unwrap();
+            assert_eq!(story.story_points, None);
+            assert_eq!(story.updated_at, None);
+        }
+
+        #[test]
+        fn read_db_should_upgrade_older_files_and_keep_a_backup() {
+            let dir = tempfile::tempdir().unwrap();
+            let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+            let file_contents = r#"{ "last_item_id": 1, "epics": { "1": { "id": 1, "name": "epic 1", "description": "", "status": "Open", "stories": [] } }, "stories": {} }"#;
+            std::fs::write(&file_path, file_contents).unwrap();
+
+            let db = JSONFileDatabase { file_path: file_path.clone() };
+            let db_state = db.read_db().unwrap();
+
+            assert_eq!(db_state.schema_version, SCHEMA_VERSION);
+            assert_eq!(db_state.epics.get(&1).unwrap().name, "epic 1".to_owned());
+
+            let backup = std::fs::read_to_string(format!("{}.v0.bak", file_path)).unwrap();
+            assert_eq!(backup, file_contents);
+
+            let upgraded: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&file_path).unwrap()).unwrap();
+            assert_eq!(upgraded["schema_version"], serde_json::json!(SCHEMA_VERSION));
+            assert_eq!(upgraded["epics"]["1"].get("id"), None);
+        }
+
+        #[test]
+        fn read_db_should_fail_for_newer_schema_version() {
+            let dir = tempfile::tempdir().unwrap();
+            let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+            let file_contents = format!(r#"{{ "schema_version": {}, "last_item_id": 0, "epics": {{}}, "stories": {{}} }}"#, SCHEMA_VERSION + 1);
+            std::fs::write(&file_path, &file_contents).unwrap();
+
+            let db = JSONFileDatabase { file_path: file_path.clone() };
+            assert!(db.read_db().is_err());
+
+            // The file must not be touched
+            assert_eq!(std::fs::read_to_string(&file_path).unwrap(), file_contents);
+        }
+
+        #[test]
+        fn write_db_should_work() {
+            let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+
+            let file_contents = r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#;
+            write!(tmpfile, "{}", file_contents).unwrap();
+
+            let file_path = "./data/write_db_should_work.json".to_owned();
+
+            let path = tmpfile.into_temp_path();
+            path.persist(&file_path).unwrap();
+
+            let db = JSONFileDatabase { file_path: file_path.clone() };
+
+            let story = Story::new("epic 1".to_owned(), "epic 1".to_owned());
+            let epic = Epic { stories: vec![2], ..Epic::new("epic 1".to_owned(), "epic 1".to_owned()) };
+
+            let mut stories = HashMap::new();
+            stories.insert(2, story);
+
+            let mut epics = HashMap::new();
+            epics.insert(1, epic);
+
+            let state = DBState { last_item_id: 2, epics, stories, ..Default::default() };
+
+            let write_result = db.write_db(&state);
+            let read_result = db.read_db().unwrap();
+
+            remove_file(file_path).unwrap();
+
+            assert!(write_result.is_ok());
+            assert_eq!(read_result, state);
+        }
+
+        #[test]
+        fn history_should_append_to_jsonl_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+            let db = JSONFileDatabase { file_path: file_path.clone() };
+            assert!(db.read_history().unwrap().is_empty());
+
+            let event = HistoryEvent { timestamp: chrono::Utc::now(), actor: "alice".to_owned(), item_type: ItemType::Epic, item_id: 1, kind: EventKind::Created };
+            db.append_history(std::slice::from_ref(&event)).unwrap();
+            db.append_history(&[event.clone(), event.clone()]).unwrap();
+
+            assert_eq!(db.read_history().unwrap(), vec![event.clone(), event.clone(), event]);
+            assert_eq!(std::fs::read_to_string(format!("{}.history.jsonl", file_path)).unwrap().lines().count(), 3);
+        }
+
+        #[test]
+        fn write_db_should_not_leave_temp_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+
+            let db = JSONFileDatabase { file_path: file_path.clone() };
+            let state = DBState::default();
+
+            assert!(db.write_db(&state).is_ok());
+            assert_eq!(db.read_db().unwrap(), state);
+            assert!(!std::path::Path::new(&format!("{}.tmp", file_path)).exists());
+        }
+
+        #[test]
+        fn jira_database_should_error_while_another_process_holds_the_lock() {
+            let dir = tempfile::tempdir().unwrap();
+            let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+            std::fs::write(&file_path, r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#).unwrap();
+
+            let db = JiraDatabase::new(file_path.clone());
+            let (release, lock) = hold_lock_elsewhere(JSONFileDatabase { file_path });
+
+            let result = db.create_epic(Epic::new("".to_owned(), "".to_owned()));
+            assert!(result.is_err());
+
+            release.send(()).unwrap();
+            lock.join().unwrap();
+            assert!(db.create_epic(Epic::new("".to_owned(), "".to_owned())).is_ok());
+        }
+
+        #[test]
+        fn read_db_should_only_upgrade_under_the_lock() {
+            let dir = tempfile::tempdir().unwrap();
+            let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+            let file_contents = r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#;
+            std::fs::write(&file_path, file_contents).unwrap();
+
+            let db = JSONFileDatabase { file_path: file_path.clone() };
+            let (release, lock) = hold_lock_elsewhere(JSONFileDatabase { file_path: file_path.clone() });
+
+            //Another process is writing, so the file is neither backed up nor upgraded
+            assert!(db.read_db().is_err());
+            assert_eq!(std::fs::read_to_string(&file_path).unwrap(), file_contents);
+            assert!(!std::path::Path::new(&format!("{}.v0.bak", file_path)).exists());
+
+            release.send(()).unwrap();
+            lock.join().unwrap();
+
+            //Transactions read while holding the lock themselves
+            let db = JiraDatabase::with_database(Box::new(db));
+            db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            assert_eq!(std::fs::read_to_string(format!("{}.v0.bak", file_path)).unwrap(), file_contents);
+            assert_eq!(db.read_db().unwrap().schema_version, SCHEMA_VERSION);
+        }
+
+        // Holds the lock of `other_process` on another thread, like another jiracli process would, until released
+        fn hold_lock_elsewhere(other_process: JSONFileDatabase) -> (std::sync::mpsc::Sender<()>, std::thread::JoinHandle<()>) {
+            let (locked_sender, locked) = std::sync::mpsc::channel();
+            let (release, released) = std::sync::mpsc::channel::<()>();
+            let lock = std::thread::spawn(move || {
+                let _lock = other_process.lock().unwrap();
+                locked_sender.send(()).unwrap();
+                let _ = released.recv();
+            });
+            locked.recv().unwrap();
+
+            (release, lock)
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,84 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+
+use crate::models::DBState;
+
+use super::NotFound;
+
+/// How far along the stories of an epic are. Resolved and closed stories count as done.
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+    pub points_done: u32,
+    pub points_total: u32,
+}
+
+impl Progress {
+    pub fn open(&self) -> usize {
+        self.total - self.done
+    }
+
+    pub fn points_remaining(&self) -> u32 {
+        self.points_total - self.points_done
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{} resolved", self.done, self.total)?;
+        if self.points_total > 0 {
+            write!(f, ", {}/{} points left", self.points_remaining(), self.points_total)?;
+        }
+        Ok(())
+    }
+}
+
+impl DBState {
+    pub fn epic_progress(&self, epic_id: u32) -> Result<Progress> {
+        let epic = self.epics.get(&epic_id).ok_or(NotFound::epic(epic_id))?;
+
+        //Dangling story ids are left to `jiracli doctor`
+        Ok(epic.stories.iter()
+            .filter_map(|story_id| self.stories.get(story_id))
+            .fold(Progress::default(), |progress, story| {
+                let points = story.story_points.unwrap_or(0);
+                let done = story.status.is_done();
+
+                Progress {
+                    done: progress.done + usize::from(done),
+                    total: progress.total + 1,
+                    points_done: progress.points_done + if done { points } else { 0 },
+                    points_total: progress.points_total + points,
+                }
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, Status, Story};
+
+    #[test]
+    fn epic_progress_should_count_done_stories_and_points() {
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+
+        assert_eq!(db_state.epic_progress(epic_id).unwrap().to_string(), "0/0 resolved");
+
+        for (status, points) in [(Status::Open, Some(5)), (Status::Resolved, Some(3)), (Status::Closed, None), (Status::InProgress, Some(2))] {
+            let story_id = db_state.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+            db_state.update_story_status(story_id, status).unwrap();
+            db_state.update_story_points(story_id, points).unwrap();
+        }
+
+        let progress = db_state.epic_progress(epic_id).unwrap();
+        assert_eq!(progress, Progress { done: 2, total: 4, points_done: 3, points_total: 10 });
+        assert_eq!(progress.open(), 2);
+        assert_eq!(progress.to_string(), "2/4 resolved, 7/10 points left");
+
+        assert!(db_state.epic_progress(999).is_err());
+    }
+}
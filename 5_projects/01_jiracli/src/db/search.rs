@@ -0,0 +1,205 @@
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::normalize_label;
+use crate::models::{DBState, Priority, Status};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ItemType {
+    Epic,
+    Story,
+}
+
+/// A parsed search query, e.g. `login status:inprogress epic:2`.
+/// Free text terms must all appear in the name or description, filters must all match.
+/// Only `status:`, `epic:`, `type:`, `assignee:`, `priority:` and `label:` are filters, other words are searched as text.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    pub status: Option<Status>,
+    pub epic_id: Option<u32>,
+    pub item_type: Option<ItemType>,
+    pub assignee: Option<String>,
+    pub priority: Option<Priority>,
+    pub label: Option<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut search_query = SearchQuery::default();
+
+        for token in query.split_whitespace() {
+            match token.split_once(':') {
+                Some(("status", value)) => search_query.status = Some(value.parse()?),
+                Some(("epic", value)) => search_query.epic_id = Some(value.parse().map_err(|_| anyhow!("Invalid epic id '{}'.", value))?),
+                Some(("type", "epic")) => search_query.item_type = Some(ItemType::Epic),
+                Some(("type", "story")) => search_query.item_type = Some(ItemType::Story),
+                Some(("type", value)) => return Err(anyhow!("Invalid type '{}'. Expected one of: epic, story.", value)),
+                Some(("assignee", value)) => search_query.assignee = Some(value.to_lowercase()),
+                Some(("priority", value)) => search_query.priority = Some(value.parse()?),
+                Some(("label", value)) => search_query.label = Some(normalize_label(value)?),
+                //Only the keys above are filters, anything else with a colon is text, e.g. "re: login" or "10:30"
+                _ => search_query.terms.push(token.to_lowercase()),
+            }
+        }
+
+        Ok(search_query)
+    }
+
+    fn matches(&self, candidate: &Candidate) -> bool {
+        let text = format!("{}\n{}", candidate.name, candidate.description).to_lowercase();
+
+        self.terms.iter().all(|term| text.contains(term.as_str()))
+            && self.status.as_ref().is_none_or(|s| s == candidate.status)
+            && self.epic_id.is_none_or(|id| id == candidate.epic_id)
+            && self.item_type.is_none_or(|t| t == candidate.item_type)
+            && self.assignee.as_ref().is_none_or(|a| candidate.assignee.as_ref().is_some_and(|assignee| assignee.to_lowercase() == *a))
+            && self.priority.as_ref().is_none_or(|p| p == candidate.priority)
+            && self.label.as_ref().is_none_or(|l| candidate.labels.contains(l))
+    }
+}
+
+// The searchable fields of an epic or a story
+struct Candidate<'a> {
+    item_type: ItemType,
+    epic_id: u32,
+    name: &'a str,
+    description: &'a str,
+    status: &'a Status,
+    assignee: &'a Option<String>,
+    priority: &'a Priority,
+    labels: &'a [String],
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SearchHit {
+    pub item_type: ItemType,
+    pub id: u32,
+    // The epic itself for epic hits, the owning epic for story hits
+    pub epic_id: u32,
+}
+
+impl DBState {
+    /// Returns the matching epics and stories sorted by id.
+    /// Stories that don't belong to any epic can't be navigated to and are skipped.
+    pub fn search(&self, query: &SearchQuery) -> Vec<SearchHit> {
+        let epic_hits = self.epics.iter()
+            .filter(|(epic_id, epic)| query.matches(&Candidate {
+                item_type: ItemType::Epic,
+                epic_id: **epic_id,
+                name: &epic.name,
+                description: &epic.description,
+                status: &epic.status,
+                assignee: &epic.assignee,
+                priority: &epic.priority,
+                labels: &epic.labels,
+            }))
+            .map(|(epic_id, _)| SearchHit { item_type: ItemType::Epic, id: *epic_id, epic_id: *epic_id });
+
+        let story_hits = self.epics.iter()
+            .flat_map(|(epic_id, epic)| epic.stories.iter().map(move |story_id| (*epic_id, *story_id)))
+            .filter_map(|(epic_id, story_id)| self.stories.get(&story_id).map(|story| (epic_id, story_id, story)))
+            .filter(|(epic_id, _, story)| query.matches(&Candidate {
+                item_type: ItemType::Story,
+                epic_id: *epic_id,
+                name: &story.name,
+                description: &story.description,
+                status: &story.status,
+                assignee: &story.assignee,
+                priority: &story.priority,
+                labels: &story.labels,
+            }))
+            .map(|(epic_id, story_id, _)| SearchHit { item_type: ItemType::Story, id: story_id, epic_id });
+
+        epic_hits.chain(story_hits).sorted_by_key(|hit| hit.id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, Story};
+
+    fn sample_state() -> DBState {
+        let mut db_state = DBState::default();
+
+        let epic_id = db_state.create_epic(Epic::new("Login page".to_owned(), "Sign in for users".to_owned()));
+        let story_id = db_state.create_story(Story::new("Login form".to_owned(), "Username and password".to_owned()), epic_id).unwrap();
+        db_state.update_story_status(story_id, Status::InProgress).unwrap();
+        db_state.update_story_assignee(story_id, Some("Alice".to_owned())).unwrap();
+        let story_id = db_state.create_story(Story::new("Reset password".to_owned(), "Email a reset link".to_owned()), epic_id).unwrap();
+        db_state.add_story_label(story_id, "bug").unwrap();
+
+        let epic_id = db_state.create_epic(Epic::new("Billing".to_owned(), "".to_owned()));
+        db_state.create_story(Story::new("Invoices".to_owned(), "Download invoice as pdf".to_owned()), epic_id).unwrap();
+
+        db_state
+    }
+
+    fn hit_ids(db_state: &DBState, query: &str) -> Vec<u32> {
+        db_state.search(&SearchQuery::parse(query).unwrap()).iter().map(|hit| hit.id).collect()
+    }
+
+    #[test]
+    fn parse_should_split_terms_and_filters() {
+        let query = SearchQuery::parse("Login status:in-progress epic:2 type:story assignee:Alice priority:high").unwrap();
+
+        assert_eq!(query.terms, vec!["login".to_owned()]);
+        assert_eq!(query.status, Some(Status::InProgress));
+        assert_eq!(query.epic_id, Some(2));
+        assert_eq!(query.item_type, Some(ItemType::Story));
+        assert_eq!(query.assignee, Some("alice".to_owned()));
+        assert_eq!(query.priority, Some(Priority::High));
+    }
+
+    #[test]
+    fn parse_should_error_on_invalid_filters() {
+        assert!(SearchQuery::parse("status:done").is_err());
+        assert!(SearchQuery::parse("epic:abc").is_err());
+        assert!(SearchQuery::parse("type:task").is_err());
+    }
+
+    #[test]
+    fn parse_should_search_unknown_keys_as_text() {
+        let query = SearchQuery::parse("RE: login owner:alice 10:30 status:open label:Bug").unwrap();
+
+        assert_eq!(query.terms, vec!["re:".to_owned(), "login".to_owned(), "owner:alice".to_owned(), "10:30".to_owned()]);
+        assert_eq!(query.status, Some(Status::Open));
+        assert_eq!(query.label, Some("bug".to_owned()));
+    }
+
+    #[test]
+    fn search_should_match_names_and_descriptions() {
+        let db_state = sample_state();
+
+        assert_eq!(hit_ids(&db_state, "login"), vec![1, 2]);
+        assert_eq!(hit_ids(&db_state, "PASSWORD"), vec![2, 3]);
+        assert_eq!(hit_ids(&db_state, "reset password"), vec![3]);
+        assert_eq!(hit_ids(&db_state, "nothing-matches"), Vec::<u32>::new());
+        assert_eq!(hit_ids(&db_state, ""), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn search_should_apply_filters() {
+        let db_state = sample_state();
+
+        assert_eq!(hit_ids(&db_state, "status:inprogress"), vec![2]);
+        assert_eq!(hit_ids(&db_state, "epic:4"), vec![4, 5]);
+        assert_eq!(hit_ids(&db_state, "type:story epic:1"), vec![2, 3]);
+        assert_eq!(hit_ids(&db_state, "type:epic"), vec![1, 4]);
+        assert_eq!(hit_ids(&db_state, "assignee:alice"), vec![2]);
+        assert_eq!(hit_ids(&db_state, "password status:open"), vec![3]);
+        assert_eq!(hit_ids(&db_state, "label:bug"), vec![3]);
+        assert_eq!(hit_ids(&db_state, "re: password"), Vec::<u32>::new());
+        assert_eq!(hit_ids(&db_state, "reset link"), vec![3]);
+    }
+
+    #[test]
+    fn search_should_return_owning_epic_for_stories() {
+        let db_state = sample_state();
+        let hits = db_state.search(&SearchQuery::parse("invoice").unwrap());
+
+        assert_eq!(hits, vec![SearchHit { item_type: ItemType::Story, id: 5, epic_id: 4 }]);
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{Database, DatabaseLock, EventKind, HistoryEvent, ItemType};
+use crate::models::{Comment, DBState, Epic, Priority, Status, Story};
+
+// Each entry upgrades the schema by one version. The current version is kept in `PRAGMA user_version`.
+const MIGRATIONS: &[&str] = &[
+    // v1: epics, stories and the epic -> story relation
+    "CREATE TABLE meta (
+        key TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );
+    CREATE TABLE epics (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+    CREATE TABLE stories (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+    CREATE TABLE epic_stories (
+        epic_id INTEGER NOT NULL,
+        story_id INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (epic_id, story_id)
+    );
+    INSERT INTO meta (key, value) VALUES ('last_item_id', 0);",
+    // v2: assignee, priority, story points and timestamps
+    "ALTER TABLE epics ADD COLUMN assignee TEXT;
+    ALTER TABLE epics ADD COLUMN priority TEXT NOT NULL DEFAULT 'Medium';
+    ALTER TABLE epics ADD COLUMN created_at TEXT;
+    ALTER TABLE epics ADD COLUMN updated_at TEXT;
+    ALTER TABLE stories ADD COLUMN assignee TEXT;
+    ALTER TABLE stories ADD COLUMN priority TEXT NOT NULL DEFAULT 'Medium';
+    ALTER TABLE stories ADD COLUMN story_points INTEGER;
+    ALTER TABLE stories ADD COLUMN created_at TEXT;
+    ALTER TABLE stories ADD COLUMN updated_at TEXT;",
+    // v3: append-only history of changes
+    "CREATE TABLE history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        actor TEXT NOT NULL,
+        item_type TEXT NOT NULL,
+        item_id INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        field TEXT,
+        from_value TEXT,
+        to_value TEXT
+    );
+    CREATE INDEX history_item ON history (item_type, item_id);",
+    // v4: comments on epics and stories
+    "CREATE TABLE comments (
+        id INTEGER PRIMARY KEY,
+        item_id INTEGER NOT NULL,
+        author TEXT NOT NULL,
+        body TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        reply_to INTEGER
+    );
+    CREATE INDEX comments_item ON comments (item_id);",
+    // v5: labels of epics and stories
+    "CREATE TABLE labels (
+        item_id INTEGER NOT NULL,
+        label TEXT NOT NULL,
+        PRIMARY KEY (item_id, label)
+    );",
+    // v6: blocked-by links between stories
+    "CREATE TABLE story_blockers (
+        story_id INTEGER NOT NULL,
+        blocker_id INTEGER NOT NULL,
+        PRIMARY KEY (story_id, blocker_id)
+    );",
+];
+
+pub struct SqliteDatabase {
+    pub file_path: String,
+    conn: Connection,
+}
+
+impl SqliteDatabase {
+    pub fn new(file_path: String) -> Result<Self> {
+        let conn = Connection::open(&file_path).with_context(|| format!("Unable to open sqlite db file: {}", file_path))?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+
+        let db = SqliteDatabase { file_path, conn };
+        db.migrate()?;
+
+        Ok(db)
+    }
+
+    fn migrate(&self) -> Result<()> {
+        let version: u32 = self.conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        for (version, migration) in (1..).zip(MIGRATIONS.iter()).skip(version as usize) {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(migration).with_context(|| format!("Unable to migrate sqlite db to version {}", version))?;
+            tx.pragma_update(None, "user_version", version)?;
+            tx.commit()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Database for SqliteDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        load_state(&self.conn)
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        // Without the state it was read from, replace every row inside a single transaction.
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(
+            "DELETE FROM epics; DELETE FROM epic_stories; DELETE FROM stories; DELETE FROM labels; DELETE FROM story_blockers; DELETE FROM comments;",
+        )?;
+        tx.execute("UPDATE meta SET value = ?1 WHERE key = 'last_item_id'", params![db_state.last_item_id])?;
+        write_rows(&tx, &DBState::default(), db_state)?;
+
+        tx.commit().with_context(|| format!("Unable to write sqlite db file into disk: \nPath: {}\n", self.file_path))
+    }
+
+    fn write_changes(&self, before: &DBState, db_state: &DBState) -> Result<()> {
+        // Only touch the rows that differ from the state the transaction read, inside a single transaction.
+        let tx = self.conn.unchecked_transaction()?;
+        write_rows(&tx, before, db_state)?;
+
+        tx.commit().with_context(|| format!("Unable to write sqlite db file into disk: \nPath: {}\n", self.file_path))
+    }
+
+    fn append_history(&self, events: &[HistoryEvent]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        for event in events {
+            let (kind, field, from, to) = match &event.kind {
+                EventKind::Created => ("Created", None, None, None),
+                EventKind::Deleted => ("Deleted", None, None, None),
+                EventKind::Changed { field, from, to } => ("Changed", Some(field), Some(from), Some(to)),
+            };
+            tx.execute(
+                "INSERT INTO history (timestamp, actor, item_type, item_id, kind, field, from_value, to_value)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![event.timestamp, event.actor, event.item_type, event.item_id, kind, field, from, to],
+            )?;
+        }
+
+        tx.commit().with_context(|| format!("Unable to write history into sqlite db file: {}", self.file_path))
+    }
+
+    fn read_history(&self) -> Result<Vec<HistoryEvent>> {
+        let mut stmt = self.conn.prepare("SELECT timestamp, actor, item_type, item_id, kind, field, from_value, to_value FROM history ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            let kind = match row.get::<_, String>(4)?.as_str() {
+                "Created" => EventKind::Created,
+                "Deleted" => EventKind::Deleted,
+                _ => EventKind::Changed { field: row.get(5)?, from: row.get(6)?, to: row.get(7)? },
+            };
+            Ok(HistoryEvent { timestamp: row.get(0)?, actor: row.get(1)?, item_type: row.get(2)?, item_id: row.get(3)?, kind })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<HistoryEvent>>>()?)
+    }
+
+    fn lock(&self) -> Result<DatabaseLock> {
+        DatabaseLock::acquire(&self.file_path)
+    }
+
+    fn file_path(&self) -> Option<&str> {
+        Some(&self.file_path)
+    }
+}
+
+// Writes the rows that differ between `before`, the state currently stored, and `after`
+fn write_rows(conn: &Connection, before: &DBState, after: &DBState) -> Result<()> {
+    for epic_id in before.epics.keys().filter(|id| !after.epics.contains_key(id)) {
+        conn.execute("DELETE FROM epics WHERE id = ?1", params![epic_id])?;
+        conn.execute("DELETE FROM epic_stories WHERE epic_id = ?1", params![epic_id])?;
+        conn.execute("DELETE FROM labels WHERE item_id = ?1", params![epic_id])?;
+    }
+
+    for (epic_id, epic) in &after.epics {
+        let before_epic = before.epics.get(epic_id);
+        if before_epic == Some(epic) {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO epics (id, name, description, status, assignee, priority, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![epic_id, epic.name, epic.description, epic.status, epic.assignee, epic.priority, epic.created_at, epic.updated_at],
+        )?;
+
+        if before_epic.map(|e| &e.stories) != Some(&epic.stories) {
+            conn.execute("DELETE FROM epic_stories WHERE epic_id = ?1", params![epic_id])?;
+            for (position, story_id) in (0u32..).zip(&epic.stories) {
+                conn.execute(
+                    "INSERT OR REPLACE INTO epic_stories (epic_id, story_id, position) VALUES (?1, ?2, ?3)",
+                    params![epic_id, story_id, position],
+                )?;
+            }
+        }
+
+        if before_epic.map(|e| &e.labels) != Some(&epic.labels) {
+            write_labels(conn, *epic_id, &epic.labels)?;
+        }
+    }
+
+    for story_id in before.stories.keys().filter(|id| !after.stories.contains_key(id)) {
+        conn.execute("DELETE FROM stories WHERE id = ?1", params![story_id])?;
+        conn.execute("DELETE FROM labels WHERE item_id = ?1", params![story_id])?;
+        conn.execute("DELETE FROM story_blockers WHERE story_id = ?1", params![story_id])?;
+    }
+
+    for (story_id, story) in &after.stories {
+        let before_story = before.stories.get(story_id);
+        if before_story == Some(story) {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO stories (id, name, description, status, assignee, priority, story_points, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![story_id, story.name, story.description, story.status, story.assignee, story.priority, story.story_points, story.created_at, story.updated_at],
+        )?;
+
+        if before_story.map(|s| &s.labels) != Some(&story.labels) {
+            write_labels(conn, *story_id, &story.labels)?;
+        }
+
+        if before_story.map(|s| &s.blocked_by) != Some(&story.blocked_by) {
+            conn.execute("DELETE FROM story_blockers WHERE story_id = ?1", params![story_id])?;
+            for blocker_id in &story.blocked_by {
+                conn.execute("INSERT OR REPLACE INTO story_blockers (story_id, blocker_id) VALUES (?1, ?2)", params![story_id, blocker_id])?;
+            }
+        }
+    }
+
+    for comment_id in before.comments.keys().filter(|id| !after.comments.contains_key(id)) {
+        conn.execute("DELETE FROM comments WHERE id = ?1", params![comment_id])?;
+    }
+
+    for (comment_id, comment) in &after.comments {
+        if before.comments.get(comment_id) == Some(comment) {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO comments (id, item_id, author, body, created_at, reply_to) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![comment_id, comment.item_id, comment.author, comment.body, comment.created_at, comment.reply_to],
+        )?;
+    }
+
+    if before.last_item_id != after.last_item_id {
+        conn.execute("UPDATE meta SET value = ?1 WHERE key = 'last_item_id'", params![after.last_item_id])?;
+    }
+
+    Ok(())
+}
+
+// Replaces the labels of one epic or story
+fn write_labels(conn: &Connection, item_id: u32, labels: &[String]) -> Result<()> {
+    conn.execute("DELETE FROM labels WHERE item_id = ?1", params![item_id])?;
+    for label in labels {
+        conn.execute("INSERT OR REPLACE INTO labels (item_id, label) VALUES (?1, ?2)", params![item_id, label])?;
+    }
+
+    Ok(())
+}
+
+fn load_state(conn: &Connection) -> Result<DBState> {
+    let last_item_id: u32 = conn
+        .query_row("SELECT value FROM meta WHERE key = 'last_item_id'", [], |row| row.get(0))
+        .optional()?
+        .unwrap_or(0);
+
+    let mut epics = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, name, description, status, assignee, priority, created_at, updated_at FROM epics")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, u32>(0)?, Epic {
+            name: row.get(1)?,
+            description: row.get(2)?,
+            status: row.get(3)?,
+            stories: vec![],
+            assignee: row.get(4)?,
+            priority: row.get(5)?,
+            labels: vec![],
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        }))
+    })?;
+    for row in rows {
+        let (epic_id, epic) = row?;
+        epics.insert(epic_id, epic);
+    }
+
+    let mut stmt = conn.prepare("SELECT epic_id, story_id FROM epic_stories ORDER BY epic_id, position")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, u32>(0)?, row.get::<_, u32>(1)?)))?;
+    for row in rows {
+        let (epic_id, story_id) = row?;
+        if let Some(epic) = epics.get_mut(&epic_id) {
+            epic.stories.push(story_id);
+        }
+    }
+
+    let mut stories = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, name, description, status, assignee, priority, story_points, created_at, updated_at FROM stories")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, u32>(0)?, Story {
+            name: row.get(1)?,
+            description: row.get(2)?,
+            status: row.get(3)?,
+            assignee: row.get(4)?,
+            priority: row.get(5)?,
+            story_points: row.get(6)?,
+            labels: vec![],
+            blocked_by: vec![],
+            created_at: row.get(7)?,
+            updated_at: row.get(8)?,
+        }))
+    })?;
+    for row in rows {
+        let (story_id, story) = row?;
+        stories.insert(story_id, story);
+    }
+
+    let mut stmt = conn.prepare("SELECT story_id, blocker_id FROM story_blockers ORDER BY story_id, blocker_id")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, u32>(0)?, row.get::<_, u32>(1)?)))?;
+    for row in rows {
+        let (story_id, blocker_id) = row?;
+        if let Some(story) = stories.get_mut(&story_id) {
+            story.blocked_by.push(blocker_id);
+        }
+    }
+
+    let mut stmt = conn.prepare("SELECT item_id, label FROM labels ORDER BY item_id, label")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (item_id, label) = row?;
+        if let Some(epic) = epics.get_mut(&item_id) {
+            epic.labels.push(label);
+        }
+        else if let Some(story) = stories.get_mut(&item_id) {
+            story.labels.push(label);
+        }
+    }
+
+    let mut comments = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, item_id, author, body, created_at, reply_to FROM comments")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, u32>(0)?, Comment {
+            item_id: row.get(1)?,
+            author: row.get(2)?,
+            body: row.get(3)?,
+            created_at: row.get(4)?,
+            reply_to: row.get(5)?,
+        }))
+    })?;
+    for row in rows {
+        let (comment_id, comment) = row?;
+        comments.insert(comment_id, comment);
+    }
+
+    Ok(DBState { last_item_id, epics, stories, comments, ..Default::default() })
+}
+
+impl ToSql for Status {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        // Stored with the same names serde uses for db.json
+        let name = match self {
+            Status::Open => "Open",
+            Status::InProgress => "InProgress",
+            Status::Resolved => "Resolved",
+            Status::Closed => "Closed",
+        };
+        Ok(ToSqlOutput::from(name))
+    }
+}
+
+impl FromSql for Status {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "Open" => Ok(Status::Open),
+            "InProgress" => Ok(Status::InProgress),
+            "Resolved" => Ok(Status::Resolved),
+            "Closed" => Ok(Status::Closed),
+            other => Err(FromSqlError::Other(format!("Invalid status in sqlite db: {}", other).into())),
+        }
+    }
+}
+
+impl ToSql for Priority {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        let name = match self {
+            Priority::Low => "Low",
+            Priority::Medium => "Medium",
+            Priority::High => "High",
+            Priority::Critical => "Critical",
+        };
+        Ok(ToSqlOutput::from(name))
+    }
+}
+
+impl FromSql for Priority {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "Low" => Ok(Priority::Low),
+            "Medium" => Ok(Priority::Medium),
+            "High" => Ok(Priority::High),
+            "Critical" => Ok(Priority::Critical),
+            other => Err(FromSqlError::Other(format!("Invalid priority in sqlite db: {}", other).into())),
+        }
+    }
+}
+
+impl ToSql for ItemType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        let name = match self {
+            ItemType::Epic => "Epic",
+            ItemType::Story => "Story",
+        };
+        Ok(ToSqlOutput::from(name))
+    }
+}
+
+impl FromSql for ItemType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "Epic" => Ok(ItemType::Epic),
+            "Story" => Ok(ItemType::Story),
+            other => Err(FromSqlError::Other(format!("Invalid item type in sqlite db: {}", other).into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_db(dir: &tempfile::TempDir) -> SqliteDatabase {
+        let file_path = dir.path().join("db.sqlite").to_str().unwrap().to_owned();
+        SqliteDatabase::new(file_path).unwrap()
+    }
+
+    fn sample_state() -> DBState {
+        let mut epics = HashMap::new();
+        epics.insert(1, Epic { status: Status::InProgress, stories: vec![3, 2], labels: vec!["backend".to_owned()], ..Epic::new("epic 1".to_owned(), "epic 1".to_owned()) });
+
+        let mut stories = HashMap::new();
+        stories.insert(2, Story { assignee: Some("alice".to_owned()), story_points: Some(3), blocked_by: vec![3], ..Story::new("story 2".to_owned(), "story 2".to_owned()) });
+        stories.insert(3, Story { status: Status::Closed, priority: Priority::High, labels: vec!["bug".to_owned(), "tech-debt".to_owned()], ..Story::new("story 3".to_owned(), "story 3".to_owned()) });
+
+        let mut comments = HashMap::new();
+        comments.insert(4, Comment::new(2, "alice".to_owned(), "first".to_owned()));
+        comments.insert(5, Comment { reply_to: Some(4), ..Comment::new(2, "bob".to_owned(), "reply".to_owned()) });
+
+        DBState { last_item_id: 5, epics, stories, comments, ..Default::default() }
+    }
+
+    #[test]
+    fn read_db_should_return_empty_state_for_new_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir);
+
+        let db_state = db.read_db().unwrap();
+
+        assert_eq!(db_state.last_item_id, 0);
+        assert!(db_state.epics.is_empty());
+        assert!(db_state.stories.is_empty());
+    }
+
+    #[test]
+    fn write_db_should_work() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir);
+        let state = sample_state();
+
+        db.write_db(&state).unwrap();
+
+        // Story order within an epic must survive the round trip.
+        assert_eq!(db.read_db().unwrap(), state);
+    }
+
+    #[test]
+    fn write_db_should_update_and_delete_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir);
+        let mut state = sample_state();
+        db.write_db(&state).unwrap();
+
+        state.stories.remove(&2);
+        state.stories.get_mut(&3).unwrap().status = Status::Resolved;
+        state.stories.get_mut(&3).unwrap().labels = vec!["bug".to_owned()];
+        state.epics.get_mut(&1).unwrap().stories = vec![3];
+        state.epics.insert(6, Epic::new("epic 6".to_owned(), "".to_owned()));
+        state.comments.remove(&5);
+        state.comments.get_mut(&4).unwrap().body = "edited".to_owned();
+        state.last_item_id = 6;
+        db.write_db(&state).unwrap();
+
+        assert_eq!(db.read_db().unwrap(), state);
+
+        state.epics.remove(&1);
+        db.write_db(&state).unwrap();
+
+        assert_eq!(db.read_db().unwrap(), state);
+    }
+
+    #[test]
+    fn write_changes_should_only_touch_changed_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir);
+        let before = sample_state();
+        db.write_db(&before).unwrap();
+
+        //A row the diff does not cover is left exactly as stored
+        db.conn.execute("UPDATE stories SET name = 'renamed' WHERE id = 3", []).unwrap();
+
+        let mut after = before.clone();
+        after.stories.get_mut(&2).unwrap().status = Status::Resolved;
+        after.stories.get_mut(&2).unwrap().blocked_by.clear();
+        after.comments.remove(&5);
+        db.write_changes(&before, &after).unwrap();
+
+        let mut expected = after.clone();
+        expected.stories.get_mut(&3).unwrap().name = "renamed".to_owned();
+        assert_eq!(db.read_db().unwrap(), expected);
+    }
+
+    #[test]
+    fn history_should_work() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir);
+        assert!(db.read_history().unwrap().is_empty());
+
+        let events = vec![
+            HistoryEvent { timestamp: chrono::Utc::now(), actor: "alice".to_owned(), item_type: ItemType::Epic, item_id: 1, kind: EventKind::Created },
+            HistoryEvent { timestamp: chrono::Utc::now(), actor: "bob".to_owned(), item_type: ItemType::Story, item_id: 2, kind: EventKind::Changed { field: "status".to_owned(), from: "OPEN".to_owned(), to: "CLOSED".to_owned() } },
+        ];
+        db.append_history(&events).unwrap();
+
+        assert_eq!(new_db(&dir).read_history().unwrap(), events);
+    }
+
+    #[test]
+    fn new_should_persist_across_connections() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = sample_state();
+        new_db(&dir).write_db(&state).unwrap();
+
+        assert_eq!(new_db(&dir).read_db().unwrap(), state);
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use anyhow::{anyhow, Context, Result};
+
+use super::JiraDatabase;
+use crate::models::{Comment, DBState, Epic, Story};
+use crate::workflow::Workflow;
+
+/// The epics, stories and comments a mutation changed, as (before, after) pairs. `None` means the item didn't exist.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Change {
+    last_item_id: u32,
+    epics: HashMap<u32, (Option<Epic>, Option<Epic>)>,
+    stories: HashMap<u32, (Option<Story>, Option<Story>)>,
+    comments: HashMap<u32, (Option<Comment>, Option<Comment>)>,
+}
+
+impl Change {
+    /// Returns the change between two db states, or None if nothing changed.
+    pub fn between(before: &DBState, after: &DBState) -> Option<Self> {
+        let change = Change {
+            last_item_id: after.last_item_id,
+            epics: diff(&before.epics, &after.epics),
+            stories: diff(&before.stories, &after.stories),
+            comments: diff(&before.comments, &after.comments),
+        };
+
+        if change.epics.is_empty() && change.stories.is_empty() && change.comments.is_empty() {
+            None
+        }
+        else {
+            Some(change)
+        }
+    }
+
+    // The change that reverts this one
+    fn inverted(&self) -> Self {
+        Change {
+            last_item_id: self.last_item_id,
+            epics: self.epics.iter().map(|(id, (before, after))| (*id, (after.clone(), before.clone()))).collect(),
+            stories: self.stories.iter().map(|(id, (before, after))| (*id, (after.clone(), before.clone()))).collect(),
+            comments: self.comments.iter().map(|(id, (before, after))| (*id, (after.clone(), before.clone()))).collect(),
+        }
+    }
+
+    fn apply_to(&self, db_state: &mut DBState, workflow: &Workflow) -> Result<()> {
+        //Refuse if any of the items were changed since, e.g. by another jiracli process.
+        let epics_unchanged = self.epics.iter().all(|(id, (before, _))| db_state.epics.get(id) == before.as_ref());
+        let stories_unchanged = self.stories.iter().all(|(id, (before, _))| db_state.stories.get(id) == before.as_ref());
+        let comments_unchanged = self.comments.iter().all(|(id, (before, _))| db_state.comments.get(id) == before.as_ref());
+        if !epics_unchanged || !stories_unchanged || !comments_unchanged {
+            return Err(anyhow!("The board was changed since. Unable to undo or redo."));
+        }
+
+        //Changing a status back is a transition like any other, so it has to be allowed by the workflow too.
+        let epic_statuses = self.epics.values().filter_map(|(before, after)| Some((&before.as_ref()?.status, &after.as_ref()?.status)));
+        let story_statuses = self.stories.values().filter_map(|(before, after)| Some((&before.as_ref()?.status, &after.as_ref()?.status)));
+        epic_statuses.chain(story_statuses)
+            .try_for_each(|(from, to)| workflow.check_transition(from, to))
+            .context("Unable to undo or redo")?;
+
+        apply(&mut db_state.epics, &self.epics);
+        apply(&mut db_state.stories, &self.stories);
+        apply(&mut db_state.comments, &self.comments);
+
+        //Never hand out an id twice, even after undoing a create.
+        db_state.last_item_id = db_state.last_item_id.max(self.last_item_id);
+
+        Ok(())
+    }
+}
+
+fn diff<K: Eq + Hash + Copy, V: PartialEq + Clone>(before: &HashMap<K, V>, after: &HashMap<K, V>) -> HashMap<K, (Option<V>, Option<V>)> {
+    before.keys().chain(after.keys())
+        .filter(|key| before.get(key) != after.get(key))
+        .map(|key| (*key, (before.get(key).cloned(), after.get(key).cloned())))
+        .collect()
+}
+
+fn apply<K: Eq + Hash + Copy, V: Clone>(items: &mut HashMap<K, V>, changes: &HashMap<K, (Option<V>, Option<V>)>) {
+    for (key, (_, after)) in changes {
+        match after {
+            Some(item) => items.insert(*key, item.clone()),
+            None => items.remove(key),
+        };
+    }
+}
+
+/// Undo and redo stacks for the current session.
+#[derive(Default)]
+pub struct UndoHistory {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl UndoHistory {
+    /// Records a new mutation. Anything that was undone before can no longer be redone.
+    pub fn record(&mut self, change: Option<Change>) {
+        if let Some(change) = change {
+            self.undo.push(change);
+            self.redo.clear();
+        }
+    }
+
+    pub fn undo(&mut self, db: &JiraDatabase) -> Result<()> {
+        let change = self.undo.last().ok_or_else(|| anyhow!("Nothing to undo."))?;
+        db.transaction(|db_state| change.inverted().apply_to(db_state, &db.workflow))?;
+
+        self.redo.extend(self.undo.pop());
+        Ok(())
+    }
+
+    pub fn redo(&mut self, db: &JiraDatabase) -> Result<()> {
+        let change = self.redo.last().ok_or_else(|| anyhow!("Nothing to redo."))?;
+        db.transaction(|db_state| change.apply_to(db_state, &db.workflow))?;
+
+        self.undo.extend(self.redo.pop());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::MockDB;
+    use crate::models::Status;
+
+    // Runs a mutation and records it the same way the Navigator does
+    fn record(history: &mut UndoHistory, db: &JiraDatabase, mutation: impl FnOnce(&JiraDatabase)) {
+        let before = db.read_db().unwrap();
+        mutation(db);
+        history.record(Change::between(&before, &db.read_db().unwrap()));
+    }
+
+    #[test]
+    fn between_should_return_none_without_changes() {
+        let db_state = DBState::default();
+        assert_eq!(Change::between(&db_state, &db_state.clone()), None);
+    }
+
+    #[test]
+    fn undo_and_redo_should_work() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let mut history = UndoHistory::default();
+
+        let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+        let db_state_before = db.read_db().unwrap();
+
+        record(&mut history, &db, |db| db.delete_epic(epic_id).unwrap());
+        let db_state_after = db.read_db().unwrap();
+        assert!(db_state_after.epics.is_empty());
+
+        assert!(history.undo(&db).is_ok());
+        assert_eq!(db.read_db().unwrap(), db_state_before);
+        assert!(history.undo(&db).is_err());
+
+        assert!(history.redo(&db).is_ok());
+        assert_eq!(db.read_db().unwrap(), db_state_after);
+        assert!(history.redo(&db).is_err());
+    }
+
+    #[test]
+    fn undo_should_restore_comments() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let mut history = UndoHistory::default();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        db.add_comment(Comment::new(epic_id, "alice".to_owned(), "".to_owned())).unwrap();
+        let db_state_before = db.read_db().unwrap();
+
+        record(&mut history, &db, |db| db.delete_epic(epic_id).unwrap());
+        assert!(db.read_db().unwrap().comments.is_empty());
+
+        history.undo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap(), db_state_before);
+    }
+
+    #[test]
+    fn undo_should_not_reuse_ids() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let mut history = UndoHistory::default();
+
+        record(&mut history, &db, |db| { db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap(); });
+        history.undo(&db).unwrap();
+
+        assert!(db.read_db().unwrap().epics.is_empty());
+        assert_eq!(db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap(), 2);
+    }
+
+    #[test]
+    fn record_should_clear_redo() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let mut history = UndoHistory::default();
+
+        record(&mut history, &db, |db| { db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap(); });
+        history.undo(&db).unwrap();
+        record(&mut history, &db, |db| { db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap(); });
+
+        assert!(history.redo(&db).is_err());
+    }
+
+    #[test]
+    fn undo_should_error_if_board_changed_since() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let mut history = UndoHistory::default();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        record(&mut history, &db, |db| db.update_epic_assignee(epic_id, Some("alice".to_owned())).unwrap());
+        db.update_epic_assignee(epic_id, Some("bob".to_owned())).unwrap();
+        let db_state_before = db.read_db().unwrap();
+
+        assert!(history.undo(&db).is_err());
+        assert_eq!(db.read_db().unwrap(), db_state_before);
+    }
+
+    #[test]
+    fn undo_should_follow_the_workflow() {
+        let workflow = Workflow::parse(r#"
+            statuses = ["Open", "Closed"]
+
+            [transitions]
+            Open = ["Closed"]
+        "#).unwrap();
+        let db = JiraDatabase::with_database(Box::new(MockDB::new())).with_workflow(workflow);
+        let mut history = UndoHistory::default();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        //Closed can't go back to Open in this workflow, so neither can undo
+        record(&mut history, &db, |db| db.update_story_status(story_id, Status::Closed).unwrap());
+        let error = history.undo(&db).unwrap_err();
+        assert_eq!(format!("{:#}", error), "Unable to undo or redo: Status CLOSED can't change to OPEN in this workflow.");
+        assert_eq!(db.read_db().unwrap().stories.get(&story_id).unwrap().status, Status::Closed);
+
+        //Undoing a create doesn't change a status
+        record(&mut history, &db, |db| { db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap(); });
+        assert!(history.undo(&db).is_ok());
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+use anyhow::{Context, Result};
+use notify::event::ModifyKind;
+use notify::{recommended_watcher, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Notices when the database file changes on disk, e.g. when a teammate saves or a `git pull` updates it.
+/// Our own writes are noticed as well, which only costs an extra redraw.
+pub struct DatabaseWatcher {
+    _watcher: RecommendedWatcher,
+    changes: Receiver<()>,
+}
+
+impl DatabaseWatcher {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let file_path = Path::new(file_path);
+        let file_name = file_path.file_name().with_context(|| format!("Invalid database file: {}", file_path.display()))?.to_owned();
+
+        //Watch the directory instead of the file. The json db is replaced by a rename on every write, which a watch on the file would not survive.
+        let dir = match file_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (sender, changes) = channel();
+        let mut watcher = recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else { return };
+
+            let changes_content = matches!(event.kind, EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any));
+            if changes_content && event.paths.iter().any(|path| path.file_name() == Some(&file_name)) {
+                let _ = sender.send(());
+            }
+        }).context("Unable to start watching the database file")?;
+
+        watcher.watch(&dir, RecursiveMode::NonRecursive).with_context(|| format!("Unable to watch directory: {}", dir.display()))?;
+
+        Ok(DatabaseWatcher { _watcher: watcher, changes })
+    }
+
+    /// Whether the file changed since the last call.
+    pub fn has_changed(&self) -> bool {
+        self.changes.try_iter().count() > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn changes_within(watcher: &DatabaseWatcher, timeout: Duration) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if watcher.has_changed() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        false
+    }
+
+    #[test]
+    fn has_changed_should_notice_writes_to_the_database_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json");
+        std::fs::write(&file_path, "{}").unwrap();
+
+        let watcher = DatabaseWatcher::new(file_path.to_str().unwrap()).unwrap();
+        assert!(!watcher.has_changed());
+
+        //Other files next to the db, like the lock file, are ignored
+        std::fs::write(dir.path().join("db.json.lock"), "").unwrap();
+        assert!(!changes_within(&watcher, Duration::from_millis(300)));
+
+        //Replaced by a rename, like JSONFileDatabase::write_db does
+        std::fs::write(dir.path().join("db.json.tmp"), "{ }").unwrap();
+        std::fs::rename(dir.path().join("db.json.tmp"), &file_path).unwrap();
+        assert!(changes_within(&watcher, Duration::from_secs(5)));
+    }
+}
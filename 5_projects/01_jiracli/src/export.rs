@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::db::JiraDatabase;
+use crate::models::{DBState, Epic, Priority, Status, Story};
+
+// One csv row per story, repeating the epic columns. Epics without stories get a row with empty story columns.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(default)]
+struct CsvRow {
+    epic_id: Option<u32>,
+    epic_name: String,
+    epic_description: String,
+    epic_status: Option<String>,
+    story_id: Option<u32>,
+    story_name: Option<String>,
+    story_description: String,
+    story_status: Option<String>,
+    story_assignee: Option<String>,
+    story_priority: Option<String>,
+    story_points: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub epics: usize,
+    pub stories: usize,
+}
+
+pub fn export_csv(db_state: &DBState, writer: impl Write) -> Result<()> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+
+    for epic_id in db_state.epics.keys().sorted() {
+        let epic = &db_state.epics[epic_id];
+        let epic_row = || CsvRow {
+            epic_id: Some(*epic_id),
+            epic_name: epic.name.clone(),
+            epic_description: epic.description.clone(),
+            epic_status: Some(epic.status.to_string()),
+            ..Default::default()
+        };
+
+        if epic.stories.is_empty() {
+            csv_writer.serialize(epic_row())?;
+        }
+
+        for story_id in &epic.stories {
+            let story = db_state.stories.get(story_id).ok_or_else(|| anyhow!("Invalid Story Id in epic: {}", story_id))?;
+            csv_writer.serialize(CsvRow {
+                story_id: Some(*story_id),
+                story_name: Some(story.name.clone()),
+                story_description: story.description.clone(),
+                story_status: Some(story.status.to_string()),
+                story_assignee: story.assignee.clone(),
+                story_priority: Some(story.priority.to_string()),
+                story_points: story.story_points,
+                ..epic_row()
+            })?;
+        }
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
+pub fn export_markdown(db_state: &DBState, mut writer: impl Write) -> Result<()> {
+    writeln!(writer, "# Board")?;
+    writeln!(writer)?;
+    writeln!(writer, "{} epics, {} stories", db_state.epics.len(), db_state.stories.len())?;
+
+    for epic_id in db_state.epics.keys().sorted() {
+        let epic = &db_state.epics[epic_id];
+
+        writeln!(writer)?;
+        writeln!(writer, "## {}: {} ({})", epic_id, markdown_text(&epic.name), epic.status)?;
+        if !epic.description.is_empty() {
+            writeln!(writer)?;
+            writeln!(writer, "{}", markdown_text(&epic.description))?;
+        }
+
+        if epic.stories.is_empty() {
+            continue;
+        }
+
+        writeln!(writer)?;
+        writeln!(writer, "| id | story | status | assignee | priority | points |")?;
+        writeln!(writer, "|---|---|---|---|---|---|")?;
+        for story_id in &epic.stories {
+            let story = db_state.stories.get(story_id).ok_or_else(|| anyhow!("Invalid Story Id in epic: {}", story_id))?;
+            writeln!(writer, "| {} | {} | {} | {} | {} | {} |",
+                story_id,
+                markdown_text(&story.name),
+                story.status,
+                markdown_text(story.assignee.as_deref().unwrap_or("-")),
+                story.priority,
+                story.story_points.map_or_else(|| "-".to_owned(), |points| points.to_string()))?;
+        }
+    }
+
+    Ok(())
+}
+
+// Keeps user text from breaking the table layout
+fn markdown_text(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Creates the epics and stories of a csv file in one transaction. The file's ids are only used to group rows
+/// into epics, the items get new ids. Rows without an epic_id are grouped by epic_name. Nothing is imported
+/// unless every line is valid and the rows of each epic agree on its name, description and status.
+pub fn import_csv(db: &JiraDatabase, reader: impl Read) -> Result<ImportSummary> {
+    let mut csv_reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(reader);
+    let headers = csv_reader.headers()?.clone();
+
+    // Epics in file order, each with the line it was first seen on and its stories
+    let mut epics: Vec<(Epic, u64, Vec<Story>)> = vec![];
+    let mut epic_index: HashMap<EpicKey, usize> = HashMap::new();
+    let mut errors = vec![];
+
+    for record in csv_reader.records() {
+        let parsed = match record {
+            Ok(record) => {
+                let line = record.position().map_or(0, |position| position.line());
+                record.deserialize::<CsvRow>(Some(&headers))
+                    .map_err(anyhow::Error::from)
+                    .and_then(parse_row)
+                    .and_then(|(key, epic, story)| {
+                        //Statuses outside of the workflow could never be changed again
+                        match std::iter::once(&epic.status).chain(story.as_ref().map(|story| &story.status)).find(|status| !db.workflow.statuses().contains(status)) {
+                            Some(status) => Err(anyhow!("Status {} is not part of the workflow.", status)),
+                            None => Ok((line, key, epic, story)),
+                        }
+                    })
+                    .map_err(|error| anyhow!("line {}: {:#}", line, error))
+            }
+            Err(error) => Err(anyhow!("line {}: {}", error.position().map_or(0, |position| position.line()), csv_error_message(&error))),
+        };
+
+        let (line, key, epic, story) = match parsed {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                errors.push(error.to_string());
+                continue;
+            }
+        };
+
+        match epic_index.get(&key) {
+            Some(index) => {
+                let (first_epic, first_line, stories) = &mut epics[*index];
+                match epic_conflict(first_epic, &epic) {
+                    Some(field) => errors.push(format!("line {}: {} of epic '{}' differs from line {}", line, field, first_epic.name, first_line)),
+                    None => stories.extend(story),
+                }
+            }
+            None => {
+                epic_index.insert(key, epics.len());
+                epics.push((epic, line, story.into_iter().collect()));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(anyhow!("Unable to import, {} invalid line(s):\n{}", errors.len(), errors.join("\n")));
+    }
+
+    db.transaction(|db_state| {
+        let mut summary = ImportSummary { epics: 0, stories: 0 };
+
+        for (epic, _, stories) in epics {
+            let epic_id = db_state.create_epic(epic);
+            summary.epics += 1;
+
+            for story in stories {
+                db_state.create_story(story, epic_id)?;
+                summary.stories += 1;
+            }
+        }
+
+        Ok(summary)
+    })
+}
+
+// The csv crate's message repeats the position, which is already in the line prefix
+fn csv_error_message(error: &csv::Error) -> String {
+    match error.kind() {
+        csv::ErrorKind::UnequalLengths { expected_len, len, .. } => format!("Found {} fields but the header has {}", len, expected_len),
+        _ => error.to_string(),
+    }
+}
+
+// Identifies the epic a row belongs to
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum EpicKey {
+    Id(u32),
+    Name(String),
+}
+
+// The first epic field two rows of the same epic disagree on, if any
+fn epic_conflict(first: &Epic, other: &Epic) -> Option<&'static str> {
+    if first.name != other.name {
+        Some("epic_name")
+    }
+    else if first.description != other.description {
+        Some("epic_description")
+    }
+    else if first.status != other.status {
+        Some("epic_status")
+    }
+    else {
+        None
+    }
+}
+
+fn parse_row(row: CsvRow) -> Result<(EpicKey, Epic, Option<Story>)> {
+    if row.epic_name.is_empty() {
+        return Err(anyhow!("epic_name is required"));
+    }
+
+    let key = match row.epic_id {
+        Some(epic_id) => EpicKey::Id(epic_id),
+        None => EpicKey::Name(row.epic_name.clone()),
+    };
+
+    let epic = Epic {
+        status: row.epic_status.as_deref().map(str::parse).transpose()?.unwrap_or(Status::Open),
+        ..Epic::new(row.epic_name, row.epic_description)
+    };
+
+    let story = match row.story_name {
+        Some(story_name) => Some(Story {
+            status: row.story_status.as_deref().map(str::parse).transpose()?.unwrap_or(Status::Open),
+            assignee: row.story_assignee,
+            priority: row.story_priority.as_deref().map(str::parse::<Priority>).transpose()?.unwrap_or_default(),
+            story_points: row.story_points,
+            ..Story::new(story_name, row.story_description)
+        }),
+        None => None,
+    };
+
+    Ok((key, epic, story))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::MockDB;
+
+    fn sample_db() -> JiraDatabase {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("Login | SSO".to_owned(), "Sign in".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("Login form".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.update_story_status(story_id, Status::InProgress).unwrap();
+        db.update_story_assignee(story_id, Some("alice".to_owned())).unwrap();
+        db.update_story_points(story_id, Some(3)).unwrap();
+        db.create_epic(Epic::new("Billing".to_owned(), "".to_owned())).unwrap();
+
+        db
+    }
+
+    #[test]
+    fn export_csv_should_write_one_row_per_story() {
+        let db = sample_db();
+        let mut out = Vec::new();
+
+        export_csv(&db.read_db().unwrap(), &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "\
+epic_id,epic_name,epic_description,epic_status,story_id,story_name,story_description,story_status,story_assignee,story_priority,story_points
+1,Login | SSO,Sign in,OPEN,2,Login form,,IN PROGRESS,alice,MEDIUM,3
+3,Billing,,OPEN,,,,,,,
+");
+    }
+
+    #[test]
+    fn export_markdown_should_write_report() {
+        let db = sample_db();
+        let mut out = Vec::new();
+
+        export_markdown(&db.read_db().unwrap(), &mut out).unwrap();
+
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with("# Board\n\n2 epics, 1 stories\n"));
+        assert!(report.contains("## 1: Login \\| SSO (OPEN)\n\nSign in\n"));
+        assert!(report.contains("| 2 | Login form | IN PROGRESS | alice | MEDIUM | 3 |\n"));
+        assert!(report.contains("## 3: Billing (OPEN)\n"));
+    }
+
+    #[test]
+    fn import_csv_should_round_trip_export() {
+        let source = sample_db();
+        let mut csv = Vec::new();
+        export_csv(&source.read_db().unwrap(), &mut csv).unwrap();
+
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let summary = import_csv(&db, csv.as_slice()).unwrap();
+        assert_eq!(summary, ImportSummary { epics: 2, stories: 1 });
+
+        let source_state = source.read_db().unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&1).unwrap().name, source_state.epics.get(&1).unwrap().name);
+
+        let story = db_state.stories.get(&2).unwrap();
+        assert_eq!(story.status, Status::InProgress);
+        assert_eq!(story.assignee, Some("alice".to_owned()));
+        assert_eq!(story.story_points, Some(3));
+    }
+
+    #[test]
+    fn import_csv_should_group_rows_by_epic_name() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let csv = "epic_name,story_name,story_priority\nBacklog,first,high\nBacklog,second,\nOther,,\n";
+
+        let summary = import_csv(&db, csv.as_bytes()).unwrap();
+        assert_eq!(summary, ImportSummary { epics: 2, stories: 2 });
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&1).unwrap().stories, vec![2, 3]);
+        assert_eq!(db_state.stories.get(&2).unwrap().priority, Priority::High);
+        assert!(db_state.epics.get(&4).unwrap().stories.is_empty());
+    }
+
+    #[test]
+    fn import_csv_should_reject_statuses_outside_of_workflow() {
+        let workflow = crate::workflow::Workflow::parse("statuses = [\"Open\", \"Closed\"]\n[transitions]").unwrap();
+        let db = JiraDatabase::with_database(Box::new(MockDB::new())).with_workflow(workflow);
+        let csv = "epic_name,story_name,story_status\nBacklog,first,closed\nBacklog,second,resolved\n";
+
+        let result = import_csv(&db, csv.as_bytes());
+        assert!(result.is_err());
+        assert!(result.err().unwrap().to_string().contains("line 3: Status RESOLVED is not part of the workflow."));
+    }
+
+    #[test]
+    fn import_csv_should_report_every_invalid_line() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let csv = "epic_name,story_name,story_status,story_points\nBacklog,first,done,\n,second,,\nBacklog,third,open,many\nBacklog,fourth,open,2\n";
+
+        let result = import_csv(&db, csv.as_bytes());
+        assert!(result.is_err());
+
+        let error = result.err().unwrap().to_string();
+        assert!(error.starts_with("Unable to import, 3 invalid line(s):"));
+        assert!(error.contains("line 2: Invalid status 'done'"));
+        assert!(error.contains("line 3: epic_name is required"));
+        assert!(error.contains("line 4: "));
+
+        assert!(db.read_db().unwrap().epics.is_empty());
+    }
+
+    #[test]
+    fn import_csv_should_group_rows_by_epic_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let csv = "epic_id,epic_name,story_name
+1,Backlog,first
+5,Backlog,second
+1,Backlog,third
+";
+
+        let summary = import_csv(&db, csv.as_bytes()).unwrap();
+        assert_eq!(summary, ImportSummary { epics: 2, stories: 3 });
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&1).unwrap().stories, vec![2, 3]);
+        assert_eq!(db_state.epics.get(&4).unwrap().stories, vec![5]);
+    }
+
+    #[test]
+    fn import_csv_should_report_conflicting_epic_fields() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let csv = "epic_id,epic_name,epic_status,story_name
+1,Backlog,open,first
+1,Backlog,closed,second
+1,Renamed,open,third
+";
+
+        let error = import_csv(&db, csv.as_bytes()).unwrap_err().to_string();
+        assert!(error.starts_with("Unable to import, 2 invalid line(s):"));
+        assert!(error.contains("line 3: epic_status of epic 'Backlog' differs from line 2"));
+        assert!(error.contains("line 4: epic_name of epic 'Backlog' differs from line 2"));
+        assert!(db.read_db().unwrap().epics.is_empty());
+    }
+
+    #[test]
+    fn import_csv_should_report_line_of_malformed_rows() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let csv = "epic_name,story_name
+Backlog,first
+Backlog,second,extra
+";
+
+        let error = import_csv(&db, csv.as_bytes()).unwrap_err().to_string();
+        assert!(error.ends_with("\nline 3: Found 3 fields but the header has 2"));
+    }
+}
@@ -1,15 +1,71 @@
 use std::io;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+// How often a waiting `get_user_input_unless` checks whether to stop waiting
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+// Reads stdin on a separate thread so waiting for input can be interrupted, e.g. to redraw.
+// A line is only read when asked for, so stdin is left alone while the full screen UI owns it.
+struct StdinReader {
+    requests: Sender<()>,
+    lines: Receiver<String>,
+    // A line was asked for but not received yet
+    pending: bool,
+}
+
+fn stdin_reader() -> &'static Mutex<StdinReader> {
+    static STDIN_READER: OnceLock<Mutex<StdinReader>> = OnceLock::new();
+
+    STDIN_READER.get_or_init(|| {
+        let (requests, pending_requests) = channel::<()>();
+        let (sender, lines) = channel();
+
+        std::thread::spawn(move || {
+            for _ in pending_requests {
+                let mut line = String::new();
+                if io::stdin().read_line(&mut line).is_err() || sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Mutex::new(StdinReader { requests, lines, pending: false })
+    })
+}
 
 pub fn get_user_input() -> String {
-    let mut user_input = String::new();
+    get_user_input_unless(|| false).unwrap_or_default()
+}
 
-    io::stdin()
-        .read_line(&mut user_input)
-        .unwrap();
+/// Waits for a line of input, or returns None as soon as `interrupted` returns true.
+/// The interrupted line is not lost, the next call returns it.
+pub fn get_user_input_unless(interrupted: impl Fn() -> bool) -> Option<String> {
+    let mut reader = stdin_reader().lock().unwrap();
 
-    user_input.trim().to_string()
+    if !reader.pending {
+        let _ = reader.requests.send(());
+        reader.pending = true;
+    }
+
+    loop {
+        match reader.lines.recv_timeout(INTERRUPT_CHECK_INTERVAL) {
+            Ok(line) => {
+                reader.pending = false;
+                return Some(line.trim().to_string());
+            }
+            Err(RecvTimeoutError::Timeout) if interrupted() => return None,
+            Err(RecvTimeoutError::Timeout) => continue,
+            //stdin failed, treat it like an empty line as before
+            Err(RecvTimeoutError::Disconnected) => {
+                reader.pending = false;
+                return Some(String::new());
+            }
+        }
+    }
 }
 
 pub fn wait_for_key_press() {
-    io::stdin().read_line(&mut String::new()).unwrap();
-}
\ No newline at end of file
+    get_user_input();
+}
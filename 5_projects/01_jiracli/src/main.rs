@@ -3,6 +3,8 @@
 #![allow(unused_imports)]
 use std::rc::Rc;
 
+use clap::Parser;
+
 mod models;
 
 mod db;
@@ -17,10 +19,56 @@ use io_utils::*;
 mod navigator;
 use navigator::*;
 
+mod cli;
+use cli::Cli;
+
+mod export;
+
+mod workflow;
+
+mod tui;
+
+mod server;
+
+mod config;
+
 fn main() {
+    let cli = Cli::parse();
+
     // TODO: create database and navigator
-    let db = Rc::new(JiraDatabase::new("./data/db.json".to_owned()));
+    let db = match cli.open_board() {
+        Ok(db) => Rc::new(db),
+        Err(error) => {
+            eprintln!("Error: {:#}", error);
+            std::process::exit(1);
+        }
+    };
+
+    // Run a single non-interactive command when one is given, otherwise start the interactive loop.
+    if let Some(command) = cli.command {
+        if let Err(error) = cli::run_command(command, &db, &mut std::io::stdout()) {
+            eprintln!("Error: {:#}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Redraw when a teammate, or a git pull, changes the board on disk. The board still works without it.
+    let watcher = db.watch().unwrap_or_else(|error| {
+        eprintln!("Warning: live reload is off: {:#}", error);
+        None
+    });
+    let board_changed = || watcher.as_ref().is_some_and(|watcher| watcher.has_changed());
+
     let mut nav = Navigator::new(db);
+
+    if cli.tui {
+        if let Err(error) = tui::run(&mut nav, board_changed) {
+            eprintln!("Error: {:#}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
     
     loop {
         clearscreen::clear().unwrap();
@@ -28,24 +76,24 @@ fn main() {
         // TODO: implement the following functionality:
         // 1. get current page from navigator. If there is no current page exit the loop.
         if let Some(curr_page) = nav.get_current_page(){
-            // 2. render page
+            // 2. render page, below the name of the board it shows
+            println!("board: {}", nav.board_name());
             if let Err(error) = curr_page.draw_page(){
                 println!("Error rendering page: {}\nPress any key to continue...", error);
                 wait_for_key_press();
             }
             else {
-                // 3. get user input
-                let user_input = get_user_input();
-
-                // 4. pass input to page's input handler
-                if let Ok(Some(action)) =  curr_page.handle_input(user_input.as_str().trim_end()){
-                    // 5. if the page's input handler returns an action let the navigator process the action
-                    let mut error = false;
-                    nav.handle_action(action).unwrap_or_else(|e| error = true);
-                    if error {
-                        println!("Error at navigator handling action.");
-                        continue;
-                    }
+                // 3. get user input, or start over with the latest board when it changes on disk
+                let Some(user_input) = get_user_input_unless(board_changed) else {
+                    nav.refresh().unwrap_or_else(|error| println!("Error reloading the board: {}", error));
+                    continue;
+                };
+
+                // 4. pass input to page's input handler and let the navigator process the action.
+                // Refusals, e.g. a workflow transition or a board changed on disk, stay on screen until acknowledged.
+                if let Err(error) = nav.handle_input(user_input.as_str().trim_end()) {
+                    println!("Error: {:#}\nPress any key to continue...", error);
+                    wait_for_key_press();
                 }
             }
         } else{ // Exit Program
@@ -54,5 +102,5 @@ fn main() {
     }
 
     println!("Good Bye!!!");
-    println!("");
+    println!();
 }
\ No newline at end of file
@@ -1,24 +1,57 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Action {
     NavigateToEpicDetail { epic_id: u32 },
     NavigateToStoryDetail { epic_id: u32, story_id: u32 },
     NavigateToPreviousPage,
+    Search,
+    NavigateToBoard { epic_id: Option<u32> },
     CreateEpic,
+    UpdateEpic { epic_id: u32 },
     UpdateEpicStatus { epic_id: u32 },
+    UpdateEpicAssignee { epic_id: u32 },
+    UpdateEpicPriority { epic_id: u32 },
     DeleteEpic { epic_id: u32 },
     CreateStory { epic_id: u32 },
+    UpdateStory { story_id: u32 },
     UpdateStoryStatus { story_id: u32 },
+    UpdateStoryAssignee { story_id: u32 },
+    UpdateStoryPriority { story_id: u32 },
+    UpdateStoryPoints { story_id: u32 },
+    AdvanceStoryStatus { story_id: u32 },
+    MoveStory { epic_id: u32, story_id: u32 },
     DeleteStory { epic_id: u32, story_id: u32 },
+    AddComment { item_id: u32 },
+    DeleteComment { item_id: u32 },
+    AddEpicLabel { epic_id: u32 },
+    RemoveEpicLabel { epic_id: u32 },
+    AddStoryLabel { story_id: u32 },
+    RemoveStoryLabel { story_id: u32 },
+    AddBlocker { story_id: u32 },
+    RemoveBlocker { story_id: u32 },
+    FilterByLabel,
+    Undo,
+    Redo,
     Exit,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+impl Action {
+    // Actions that may change the board and therefore can be undone
+    pub fn is_mutation(&self) -> bool {
+        !matches!(self,
+            Self::NavigateToEpicDetail { .. } | Self::NavigateToStoryDetail { .. } | Self::NavigateToPreviousPage
+            | Self::Search | Self::NavigateToBoard { .. } | Self::FilterByLabel | Self::Undo | Self::Redo | Self::Exit)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
 pub enum Status {    
     Open,
     InProgress,
@@ -26,29 +59,104 @@ pub enum Status {
     Closed,
 }
 
+impl Status {
+    // In board order
+    pub const ALL: [Status; 4] = [Status::Open, Status::InProgress, Status::Resolved, Status::Closed];
+
+    // Counts towards an epic's progress
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Resolved | Self::Closed)
+    }
+}
+
 impl Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self{
             Self::Open=> write!(f, "OPEN"),
             Self::InProgress=> write!(f, "IN PROGRESS"),
             Self::Resolved=> write!(f, "RESOLVED"), 
-            Self::Closed=> write!(f, "CLOSED"), 
-        }       
+            Self::Closed=> write!(f, "CLOSED"),
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Accept the display names as well as the usual shell friendly spellings e.g. "in-progress"
+        match s.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "open" => Ok(Self::Open),
+            "inprogress" => Ok(Self::InProgress),
+            "resolved" => Ok(Self::Resolved),
+            "closed" => Ok(Self::Closed),
+            _ => Err(anyhow!("Invalid status '{}'. Expected one of: open, inprogress, resolved, closed.", s)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            Self::Low=> write!(f, "LOW"),
+            Self::Medium=> write!(f, "MEDIUM"),
+            Self::High=> write!(f, "HIGH"),
+            Self::Critical=> write!(f, "CRITICAL"),
+        }
     }
 }
 
+impl FromStr for Priority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            _ => Err(anyhow!("Invalid priority '{}'. Expected one of: low, medium, high, critical.", s)),
+        }
+    }
+}
+
+// Fields added after the first release are defaulted so that older db.json files still load.
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub struct Epic {
     pub name: String,
     pub description: String,
     pub status: Status,
     pub stories: Vec<u32>,
+    #[serde(default)]
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl Epic {
     pub fn new(name: String, description: String) -> Self {
         // by default the status should be set to open and the stories should be an empty vector
-        Epic { name, description, status: Status::Open, stories: vec![] }
+        let now = Some(Utc::now());
+        Epic { name, description, status: Status::Open, stories: vec![], assignee: None, priority: Priority::default(), labels: vec![], created_at: now, updated_at: now }
+    }
+
+    pub fn touch(&mut self) {
+        self.updated_at = Some(Utc::now());
     }
 }
 
@@ -57,18 +165,69 @@ pub struct Story {
     pub name: String,
     pub description: String,
     pub status: Status,
+    #[serde(default)]
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub story_points: Option<u32>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    // Ids of the stories that have to be resolved first
+    #[serde(default)]
+    pub blocked_by: Vec<u32>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl Story {
     pub fn new(name: String, description: String) -> Self {
-        Story { name, description, status: Status::Open }
+        let now = Some(Utc::now());
+        Story { name, description, status: Status::Open, assignee: None, priority: Priority::default(), story_points: None, labels: vec![], blocked_by: vec![], created_at: now, updated_at: now }
+    }
+
+    pub fn touch(&mut self) {
+        self.updated_at = Some(Utc::now());
     }
 }
 
+// A comment on an epic or a story. Epics and stories share one id space, so item_id is enough to find it.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Comment {
+    pub item_id: u32,
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    // Id of the comment this one answers, None for the start of a thread
+    #[serde(default)]
+    pub reply_to: Option<u32>,
+}
+
+impl Comment {
+    pub fn new(item_id: u32, author: String, body: String) -> Self {
+        Comment { item_id, author, body, created_at: Utc::now(), reply_to: None }
+    }
+}
+
+// Version of the stored db document. Bump it together with a new migration in db/migrations.rs.
+pub const SCHEMA_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub struct DBState {
     // This struct represents the entire db state which includes the last_item_id, epics, and stories
+    #[serde(default)]
+    pub schema_version: u32,
     pub last_item_id: u32,
     pub epics: HashMap<u32, Epic>,
     pub stories: HashMap<u32, Story>,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub comments: HashMap<u32, Comment>,
+}
+
+impl Default for DBState {
+    fn default() -> Self {
+        DBState { schema_version: SCHEMA_VERSION, last_item_id: 0, epics: HashMap::new(), stories: HashMap::new(), comments: HashMap::new() }
+    }
+}
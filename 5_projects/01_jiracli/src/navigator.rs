@@ -1,40 +1,74 @@
 use anyhow::{anyhow, Result, Context, Ok};
 use std::rc::Rc;
 
-use crate::{ui::{Page, HomePage, EpicDetail, StoryDetail, Prompts}, db::JiraDatabase, models::Action};
+use crate::{ui::{Page, HomePage, EpicDetail, StoryDetail, SearchPage, BoardPage, ListState, Prompts}, db::{current_actor, normalize_label, Change, JiraDatabase, SearchQuery, UndoHistory}, models::{Action, Comment}};
 
 pub struct Navigator {
     pages: Vec<Box<dyn Page>>,
     prompts: Prompts,
+    history: UndoHistory,
     db: Rc<JiraDatabase>
 }
 
 impl Navigator {
     pub fn new(db: Rc<JiraDatabase>) -> Self {        
-        let home_page = HomePage{db: Rc::clone(&db)};        
+        let home_page = HomePage{db: Rc::clone(&db), label: None, list: ListState::default()};        
         Navigator{
             pages: vec![Box::new(home_page)],
             prompts: Prompts::new(),
+            history: UndoHistory::default(),
             db: Rc::clone(&db),
         }
     }
 
-    pub fn get_current_page(&self) -> Option<&Box<dyn Page>> {
-        if self.pages.len() == 0{
-            return None;
-        }
+    pub fn get_current_page(&self) -> Option<&dyn Page> {
+        self.pages.last().map(|page| page.as_ref())
+    }
+
+    /// Passes user input to the current page and processes the action it returns, if any.
+    pub fn handle_input(&mut self, input: &str) -> Result<()> {
+        let Some(page) = self.get_current_page() else { return Ok(()) };
 
-        let &page = &self.pages.get(self.pages.len() - 1);
-        page
+        match page.handle_input(input)? {
+            Some(action) => self.handle_action(action),
+            None => Ok(()),
+        }
     }
 
     pub fn handle_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Undo => {
+                self.history.undo(&self.db)?;
+                self.drop_stale_pages()
+            }
+            Action::Redo => {
+                self.history.redo(&self.db)?;
+                self.drop_stale_pages()
+            }
+            action if action.is_mutation() => {
+                // Snapshot the board around the mutation so that it can be undone.
+                // Prompts show this snapshot, so the mutation is refused if the board changes while they are open.
+                let before = self.db.read_db()?;
+                let db = Rc::clone(&self.db);
+                let result = db.based_on(before.clone(), || self.run_action(action));
+                if result.is_ok() {
+                    self.history.record(Change::between(&before, &self.db.read_db()?));
+                }
+
+                result
+            }
+            action => self.run_action(action),
+        }
+    }
+
+    fn run_action(&mut self, action: Action) -> Result<()> {
         match action {
             Action::NavigateToEpicDetail { epic_id } => {
                 // Create a new EpicDetail instance and add it to the pages vector
                 let epicdetail = EpicDetail{
                     epic_id,
                     db: Rc::clone(&self.db),
+                    list: ListState::default(),
                 };
 
                 self.pages.push(Box::new(epicdetail));
@@ -51,10 +85,42 @@ impl Navigator {
             }
             Action::NavigateToPreviousPage => {               
                 // Remove the last page from the pages vector
-                if self.pages.len() > 0 {
+                if !self.pages.is_empty() {
                     self.pages.remove(self.pages.len() - 1);
                 }
             }
+            Action::Search => {
+                // Prompt the user for a query and show the results on a new SearchPage
+                let search_prompt = &self.prompts.search;
+                let query = search_prompt();
+
+                // Validate the filters up front so an invalid query never becomes a page that can't be drawn
+                SearchQuery::parse(&query)?;
+
+                self.pages.push(Box::new(SearchPage{
+                    query,
+                    db: Rc::clone(&self.db),
+                }));
+            }
+            Action::NavigateToBoard { epic_id } => {
+                // Show the stories of one epic, or of the whole board, grouped by status
+                self.pages.push(Box::new(BoardPage{
+                    epic_id,
+                    db: Rc::clone(&self.db),
+                }));
+            }
+            Action::FilterByLabel => {
+                // Prompt the user for a label and replace the home page with one that only lists matching epics
+                let filter_by_label_prompt = &self.prompts.filter_by_label;
+                let label = filter_by_label_prompt().as_deref().map(normalize_label).transpose()?;
+
+                self.pages.pop();
+                self.pages.push(Box::new(HomePage{
+                    db: Rc::clone(&self.db),
+                    label,
+                    list: ListState::default(),
+                }));
+            }
             Action::CreateEpic => {
                 // Prompt the user to create a new epic and persist it in the database
                 let new_epic_prompt = &self.prompts.create_epic;
@@ -62,14 +128,45 @@ impl Navigator {
 
                 self.db.create_epic(epic)?;
             }
+            Action::UpdateEpic { epic_id } => {
+                // Prompt the user with the current name and description and persist the changes in the database
+                let db_state = self.db.read_db()?;
+                let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Invalid Epic Id."))?;
+
+                let update_epic_prompt = &self.prompts.update_epic;
+                let (name, description) = update_epic_prompt(epic);
+
+                self.db.update_epic(epic_id, name, description)?;
+            }
             Action::UpdateEpicStatus { epic_id } => {
                 // Prompt the user to update status and persist it in the database
                 let update_epic_prompt = &self.prompts.update_status;
 
-                if let Some(status) = update_epic_prompt() {
+                // Only offer the statuses the workflow allows next
+                let db_state = self.db.read_db()?;
+                let current_status = &db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Invalid Epic Id."))?.status;
+                let statuses = self.db.workflow.transitions_from(current_status);
+                if statuses.is_empty() {
+                    return Err(anyhow!("Status {} can't be changed in this workflow.", current_status));
+                }
+
+                if let Some(status) = update_epic_prompt(&statuses) {
                     self.db.update_epic_status(epic_id, status)?;
                 }
             }
+            Action::UpdateEpicAssignee { epic_id } => {
+                // Prompt the user to update the assignee and persist it in the database
+                let update_assignee_prompt = &self.prompts.update_assignee;
+                self.db.update_epic_assignee(epic_id, update_assignee_prompt())?;
+            }
+            Action::UpdateEpicPriority { epic_id } => {
+                // Prompt the user to update the priority and persist it in the database
+                let update_priority_prompt = &self.prompts.update_priority;
+
+                if let Some(priority) = update_priority_prompt() {
+                    self.db.update_epic_priority(epic_id, priority)?;
+                }
+            }
             Action::DeleteEpic { epic_id } => {
                 // Prompt the user to delete the epic and persist it in the database
                 let delete_epic_prompt = &self.prompts.delete_epic;
@@ -86,14 +183,73 @@ impl Navigator {
 
                 self.db.create_story(story, epic_id)?;
             }
+            Action::UpdateStory { story_id } => {
+                // Prompt the user with the current name and description and persist the changes in the database
+                let db_state = self.db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Invalid Story Id."))?;
+
+                let update_story_prompt = &self.prompts.update_story;
+                let (name, description) = update_story_prompt(story);
+
+                self.db.update_story(story_id, name, description)?;
+            }
             Action::UpdateStoryStatus { story_id } => {
                 // Prompt the user to update status and persist it in the database
                 let update_story_prompt = &self.prompts.update_status;
 
-                if let Some(status) = update_story_prompt() {
+                // Only offer the statuses the workflow allows next
+                let db_state = self.db.read_db()?;
+                let current_status = &db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Invalid Story Id."))?.status;
+                let statuses = self.db.workflow.transitions_from(current_status);
+                if statuses.is_empty() {
+                    return Err(anyhow!("Status {} can't be changed in this workflow.", current_status));
+                }
+
+                if let Some(status) = update_story_prompt(&statuses) {
                     self.db.update_story_status(story_id, status)?;
                 }
             }
+            Action::UpdateStoryAssignee { story_id } => {
+                // Prompt the user to update the assignee and persist it in the database
+                let update_assignee_prompt = &self.prompts.update_assignee;
+                self.db.update_story_assignee(story_id, update_assignee_prompt())?;
+            }
+            Action::UpdateStoryPriority { story_id } => {
+                // Prompt the user to update the priority and persist it in the database
+                let update_priority_prompt = &self.prompts.update_priority;
+
+                if let Some(priority) = update_priority_prompt() {
+                    self.db.update_story_priority(story_id, priority)?;
+                }
+            }
+            Action::UpdateStoryPoints { story_id } => {
+                // Prompt the user to update the story points and persist it in the database
+                let update_story_points_prompt = &self.prompts.update_story_points;
+
+                if let Some(story_points) = update_story_points_prompt() {
+                    self.db.update_story_points(story_id, Some(story_points))?;
+                }
+            }
+            Action::AdvanceStoryStatus { story_id } => {
+                // Move the story to the next column of the board, closed stories stay where they are
+                let db_state = self.db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Invalid Story Id."))?;
+
+                if let Some(status) = self.db.workflow.next_status(&story.status) {
+                    self.db.update_story_status(story_id, status)?;
+                }
+            }
+            Action::MoveStory { epic_id, story_id } => {
+                // Prompt the user for the target epic and persist the move in the database
+                let move_story_prompt = &self.prompts.move_story;
+
+                if let Some(to_epic_id) = move_story_prompt() {
+                    self.db.move_story(story_id, epic_id, to_epic_id)?;
+
+                    // The story page belongs to the old epic, so go back to it
+                    self.pages.pop();
+                }
+            }
             Action::DeleteStory { epic_id, story_id } => {
                 // Prompt the user to delete the story and persist it in the database
                 let delete_story_prompt = &self.prompts.delete_story;
@@ -103,21 +259,118 @@ impl Navigator {
 
                 self.pages.pop();
             }
+            Action::AddEpicLabel { epic_id } => {
+                // Prompt the user for a label and persist it in the database
+                let add_label_prompt = &self.prompts.add_label;
+
+                if let Some(label) = add_label_prompt() {
+                    self.db.add_epic_label(epic_id, &label)?;
+                }
+            }
+            Action::RemoveEpicLabel { epic_id } => {
+                // Prompt the user for the label to remove and persist it in the database
+                let remove_label_prompt = &self.prompts.remove_label;
+
+                if let Some(label) = remove_label_prompt() {
+                    self.db.remove_epic_label(epic_id, &label)?;
+                }
+            }
+            Action::AddStoryLabel { story_id } => {
+                // Prompt the user for a label and persist it in the database
+                let add_label_prompt = &self.prompts.add_label;
+
+                if let Some(label) = add_label_prompt() {
+                    self.db.add_story_label(story_id, &label)?;
+                }
+            }
+            Action::RemoveStoryLabel { story_id } => {
+                // Prompt the user for the label to remove and persist it in the database
+                let remove_label_prompt = &self.prompts.remove_label;
+
+                if let Some(label) = remove_label_prompt() {
+                    self.db.remove_story_label(story_id, &label)?;
+                }
+            }
+            Action::AddBlocker { story_id } => {
+                // Prompt the user for the blocking story and persist the link in the database
+                let add_blocker_prompt = &self.prompts.add_blocker;
+
+                if let Some(blocker_id) = add_blocker_prompt() {
+                    self.db.add_blocker(story_id, blocker_id)?;
+                }
+            }
+            Action::RemoveBlocker { story_id } => {
+                // Prompt the user for the blocking story and remove the link from the database
+                let remove_blocker_prompt = &self.prompts.remove_blocker;
+
+                if let Some(blocker_id) = remove_blocker_prompt() {
+                    self.db.remove_blocker(story_id, blocker_id)?;
+                }
+            }
+            Action::AddComment { item_id } => {
+                // Prompt the user for the comment and persist it in the database, signed by the current user
+                let add_comment_prompt = &self.prompts.add_comment;
+
+                if let Some((body, reply_to)) = add_comment_prompt() {
+                    self.db.add_comment(Comment { reply_to, ..Comment::new(item_id, current_actor(), body) })?;
+                }
+            }
+            Action::DeleteComment { item_id } => {
+                // Prompt the user for the comment to delete and persist it in the database
+                let delete_comment_prompt = &self.prompts.delete_comment;
+
+                if let Some(comment_id) = delete_comment_prompt() {
+                    self.db.delete_comment(item_id, comment_id)?;
+                }
+            }
             Action::Exit => {
                 // Remove all pages from the pages vector
                 self.pages.clear();
             },
+            Action::Undo | Action::Redo => unreachable!("handled in handle_action"),
         }
 
         Ok(())
     }
 
-    // Private functions used for testing
+    /// Called when the board changed outside of jiracli. Pages read the board when drawn,
+    /// so only pages of epics and stories that no longer exist have to go.
+    pub fn refresh(&mut self) -> Result<()> {
+        self.drop_stale_pages()
+    }
+
+    // Undo and redo can remove the epic or story a page shows, so drop those pages
+    fn drop_stale_pages(&mut self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+
+        self.pages.retain(|page| {
+            if let Some(epic_detail) = page.as_any().downcast_ref::<EpicDetail>() {
+                db_state.epics.contains_key(&epic_detail.epic_id)
+            }
+            else if let Some(story_detail) = page.as_any().downcast_ref::<StoryDetail>() {
+                db_state.epics.get(&story_detail.epic_id).is_some_and(|epic| epic.stories.contains(&story_detail.story_id))
+            }
+            else if let Some(board) = page.as_any().downcast_ref::<BoardPage>() {
+                board.epic_id.is_none_or(|epic_id| db_state.epics.contains_key(&epic_id))
+            }
+            else {
+                true
+            }
+        });
+
+        Ok(())
+    }
 
-    fn get_page_count(&self) -> usize {
+    pub fn board_name(&self) -> &str {
+        &self.db.name
+    }
+
+    pub fn get_page_count(&self) -> usize {
         self.pages.len()
     }
 
+    // Private functions used for testing
+
     fn set_prompts(&mut self, prompts: Prompts) {
         self.prompts = prompts;
     }
@@ -125,12 +378,12 @@ impl Navigator {
 
 #[cfg(test)]
 mod tests {
-    use crate::{db::test_utils::MockDB, models::{Epic, Status, Story}};
+    use crate::{db::test_utils::MockDB, models::{DBState, Epic, Status, Story, Priority}, workflow::Workflow};
     use super::*;
 
     #[test]
     fn should_start_on_home_page() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let nav = Navigator::new(db);
 
         assert_eq!(nav.get_page_count(), 1);
@@ -138,12 +391,12 @@ mod tests {
         let current_page = nav.get_current_page().unwrap();
         let home_page = current_page.as_any().downcast_ref::<HomePage>();
 
-        assert_eq!(home_page.is_some(), true);
+        assert!(home_page.is_some());
     }
 
     #[test]
     fn handle_action_should_navigate_pages() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
         let mut nav = Navigator::new(db);
         
@@ -152,28 +405,28 @@ mod tests {
 
         let current_page = nav.get_current_page().unwrap();
         let epic_detail_page = current_page.as_any().downcast_ref::<EpicDetail>();
-        assert_eq!(epic_detail_page.is_some(), true);
+        assert!(epic_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToStoryDetail { epic_id: 1, story_id: 2 }).unwrap();
         assert_eq!(nav.get_page_count(), 3);
 
         let current_page = nav.get_current_page().unwrap();
         let story_detail_page = current_page.as_any().downcast_ref::<StoryDetail>();
-        assert_eq!(story_detail_page.is_some(), true);
+        assert!(story_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 2);
 
         let current_page = nav.get_current_page().unwrap();
         let epic_detail_page = current_page.as_any().downcast_ref::<EpicDetail>();
-        assert_eq!(epic_detail_page.is_some(), true);
+        assert!(epic_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 1);
 
         let current_page = nav.get_current_page().unwrap();
         let home_page = current_page.as_any().downcast_ref::<HomePage>();
-        assert_eq!(home_page.is_some(), true);
+        assert!(home_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 0);
@@ -184,7 +437,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_clear_pages_on_exit() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
         let mut nav = Navigator::new(db);
         
@@ -195,9 +448,34 @@ mod tests {
         assert_eq!(nav.get_page_count(), 0);
     }
 
+    #[test]
+    fn handle_action_should_handle_search() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.search = Box::new(|| "login status:open".to_owned());
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::Search).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        let current_page = nav.get_current_page().unwrap();
+        let search_page = current_page.as_any().downcast_ref::<SearchPage>();
+        assert_eq!(search_page.unwrap().query, "login status:open".to_owned());
+
+        let mut prompts = Prompts::new();
+        prompts.search = Box::new(|| "status:unknown".to_owned());
+        nav.set_prompts(prompts);
+
+        assert!(nav.handle_action(Action::Search).is_err());
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
     #[test]
     fn handle_action_should_handle_create_epic() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
         let mut nav = Navigator::new(Rc::clone(&db));
 
@@ -216,15 +494,37 @@ mod tests {
         assert_eq!(epic.description, "description".to_owned());
     }
 
+    #[test]
+    fn handle_action_should_handle_edit_epic() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("name".to_owned(), "description".to_owned())).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.update_epic = Box::new(|epic| (format!("{} v2", epic.name), epic.description.clone()));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateEpic { epic_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let epic = db_state.epics.get(&epic_id).unwrap();
+        assert_eq!(epic.name, "name v2".to_owned());
+        assert_eq!(epic.description, "description".to_owned());
+
+        assert!(nav.handle_action(Action::UpdateEpic { epic_id: 999 }).is_err());
+    }
+
     #[test]
     fn handle_action_should_handle_update_epic() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
 
         let mut nav = Navigator::new(Rc::clone(&db));
 
         let mut prompts = Prompts::new();
-        prompts.update_status = Box::new(|| Some(Status::InProgress));
+        prompts.update_status = Box::new(|_| Some(Status::InProgress));
 
         nav.set_prompts(prompts);
         
@@ -234,9 +534,31 @@ mod tests {
         assert_eq!(db_state.epics.get(&epic_id).unwrap().status, Status::InProgress);
     }
 
+    #[test]
+    fn handle_action_should_handle_update_epic_assignee_and_priority() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.update_assignee = Box::new(|| Some("alice".to_owned()));
+        prompts.update_priority = Box::new(|| Some(Priority::High));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateEpicAssignee { epic_id }).unwrap();
+        nav.handle_action(Action::UpdateEpicPriority { epic_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let epic = db_state.epics.get(&epic_id).unwrap();
+        assert_eq!(epic.assignee, Some("alice".to_owned()));
+        assert_eq!(epic.priority, Priority::High);
+    }
+
     #[test]
     fn handle_action_should_handle_delete_epic() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
 
         let mut nav = Navigator::new(Rc::clone(&db));
@@ -254,7 +576,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_create_story() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
 
         let mut nav = Navigator::new(Rc::clone(&db));
@@ -274,16 +596,39 @@ mod tests {
         assert_eq!(story.description, "description".to_owned());
     }
 
+    #[test]
+    fn handle_action_should_handle_edit_story() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("name".to_owned(), "description".to_owned()), epic_id).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.update_story = Box::new(|story| (story.name.clone(), "new description".to_owned()));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStory { story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let story = db_state.stories.get(&story_id).unwrap();
+        assert_eq!(story.name, "name".to_owned());
+        assert_eq!(story.description, "new description".to_owned());
+
+        assert!(nav.handle_action(Action::UpdateStory { story_id: 999 }).is_err());
+    }
+
     #[test]
     fn handle_action_should_handle_update_story() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
         let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
 
         let mut nav = Navigator::new(Rc::clone(&db));
 
         let mut prompts = Prompts::new();
-        prompts.update_status = Box::new(|| Some(Status::InProgress));
+        prompts.update_status = Box::new(|_| Some(Status::InProgress));
 
         nav.set_prompts(prompts);
         
@@ -293,9 +638,342 @@ mod tests {
         assert_eq!(db_state.stories.get(&story_id).unwrap().status, Status::InProgress);
     }
 
+    #[test]
+    fn handle_action_should_handle_update_story_fields() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.update_assignee = Box::new(|| Some("bob".to_owned()));
+        prompts.update_priority = Box::new(|| Some(Priority::Low));
+        prompts.update_story_points = Box::new(|| Some(8));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryAssignee { story_id }).unwrap();
+        nav.handle_action(Action::UpdateStoryPriority { story_id }).unwrap();
+        nav.handle_action(Action::UpdateStoryPoints { story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let story = db_state.stories.get(&story_id).unwrap();
+        assert_eq!(story.assignee, Some("bob".to_owned()));
+        assert_eq!(story.priority, Priority::Low);
+        assert_eq!(story.story_points, Some(8));
+    }
+
+    #[test]
+    fn handle_action_should_navigate_to_board() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let mut nav = Navigator::new(db);
+
+        nav.handle_action(Action::NavigateToBoard { epic_id: Some(1) }).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        let current_page = nav.get_current_page().unwrap();
+        let board_page = current_page.as_any().downcast_ref::<BoardPage>();
+        assert!(board_page.is_some());
+        assert_eq!(board_page.unwrap().epic_id, Some(1));
+    }
+
+    #[test]
+    fn handle_action_should_advance_story_status() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        for status in [Status::InProgress, Status::Resolved, Status::Closed, Status::Closed] {
+            nav.handle_action(Action::AdvanceStoryStatus { story_id }).unwrap();
+            assert_eq!(db.read_db().unwrap().stories.get(&story_id).unwrap().status, status);
+        }
+
+        assert!(nav.handle_action(Action::AdvanceStoryStatus { story_id: 999 }).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_only_offer_workflow_transitions() {
+        let workflow = Workflow::parse("statuses = [\"Open\", \"InProgress\", \"Closed\"]\n[transitions]\nOpen = [\"Closed\", \"InProgress\"]").unwrap();
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())).with_workflow(workflow));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|statuses| {
+            assert_eq!(statuses, &[Status::InProgress, Status::Closed]);
+            Some(Status::Closed)
+        });
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateEpicStatus { epic_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().epics.get(&epic_id).unwrap().status, Status::Closed);
+
+        // Closed has no transitions so there is nothing to offer
+        assert!(nav.handle_action(Action::UpdateEpicStatus { epic_id }).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_handle_move_story() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let to_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::NavigateToStoryDetail { epic_id, story_id }).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.move_story = Box::new(move || Some(to_epic_id));
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::MoveStory { epic_id, story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.epics.get(&epic_id).unwrap().stories.is_empty());
+        assert_eq!(db_state.epics.get(&to_epic_id).unwrap().stories, vec![story_id]);
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_undo_and_redo_delete_epic() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let db_state_before = db.read_db().unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.delete_epic = Box::new(|| true);
+
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+
+        nav.handle_action(Action::Undo).unwrap();
+        assert_eq!(db.read_db().unwrap(), db_state_before);
+        assert!(nav.handle_action(Action::Undo).is_err());
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        nav.handle_action(Action::Redo).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+
+        // The page of the deleted epic is dropped
+        assert_eq!(nav.get_page_count(), 1);
+        assert!(nav.handle_action(Action::Redo).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_refuse_mutations_based_on_a_stale_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+        let db = Rc::new(JiraDatabase::new(file_path.clone()));
+        db.database.write_db(&DBState::default()).unwrap();
+        let epic_id = db.create_epic(Epic::new("old".to_owned(), "".to_owned())).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        // A teammate renames the epic while our prompt is open
+        let teammate_db = JiraDatabase::new(file_path);
+        let mut prompts = Prompts::new();
+        prompts.update_epic = Box::new(move |_| {
+            teammate_db.update_epic(epic_id, "theirs".to_owned(), "".to_owned()).unwrap();
+            ("ours".to_owned(), "".to_owned())
+        });
+        nav.set_prompts(prompts);
+
+        let result = nav.handle_action(Action::UpdateEpic { epic_id });
+        assert!(result.unwrap_err().to_string().contains("changed outside of jiracli"));
+        assert_eq!(db.read_db().unwrap().epics.get(&epic_id).unwrap().name, "theirs");
+
+        // The teammate's change can't be undone from here
+        assert!(nav.handle_action(Action::Undo).is_err());
+    }
+
+    #[test]
+    fn handle_input_should_return_refusals() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+        let db = Rc::new(JiraDatabase::new(file_path.clone()));
+        db.database.write_db(&DBState::default()).unwrap();
+        let epic_id = db.create_epic(Epic::new("old".to_owned(), "".to_owned())).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+        assert_eq!(nav.handle_input("z").unwrap_err().to_string(), "Nothing to undo.");
+
+        let teammate_db = JiraDatabase::new(file_path);
+        let mut prompts = Prompts::new();
+        prompts.update_epic = Box::new(move |_| {
+            teammate_db.update_epic(epic_id, "theirs".to_owned(), "".to_owned()).unwrap();
+            ("ours".to_owned(), "".to_owned())
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_input(&epic_id.to_string()).unwrap();
+        let error = nav.handle_input("e").unwrap_err();
+        assert!(format!("{:#}", error).contains("changed outside of jiracli"));
+    }
+
+    #[test]
+    fn refresh_should_drop_pages_of_items_deleted_elsewhere() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::NavigateToStoryDetail { epic_id, story_id }).unwrap();
+
+        db.delete_story(epic_id, story_id).unwrap();
+        nav.refresh().unwrap();
+
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_not_record_navigation() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+
+        assert!(nav.handle_action(Action::Undo).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_handle_labels() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.add_label = Box::new(|| Some("Backend".to_owned()));
+        prompts.remove_label = Box::new(|| Some("backend".to_owned()));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddEpicLabel { epic_id }).unwrap();
+        nav.handle_action(Action::AddStoryLabel { story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().labels, vec!["backend".to_owned()]);
+        assert_eq!(db_state.stories.get(&story_id).unwrap().labels, vec!["backend".to_owned()]);
+
+        nav.handle_action(Action::RemoveEpicLabel { epic_id }).unwrap();
+        nav.handle_action(Action::RemoveStoryLabel { story_id }).unwrap();
+        assert!(nav.handle_action(Action::RemoveStoryLabel { story_id }).is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.epics.get(&epic_id).unwrap().labels.is_empty());
+        assert!(db_state.stories.get(&story_id).unwrap().labels.is_empty());
+    }
+
+    #[test]
+    fn handle_action_should_filter_home_page_by_label() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let mut nav = Navigator::new(db);
+
+        let mut prompts = Prompts::new();
+        prompts.filter_by_label = Box::new(|| Some("Bug".to_owned()));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::FilterByLabel).unwrap();
+        assert_eq!(nav.get_page_count(), 1);
+
+        let home_page = nav.get_current_page().unwrap().as_any().downcast_ref::<HomePage>();
+        assert_eq!(home_page.unwrap().label, Some("bug".to_owned()));
+
+        let mut prompts = Prompts::new();
+        prompts.filter_by_label = Box::new(|| None);
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::FilterByLabel).unwrap();
+        let home_page = nav.get_current_page().unwrap().as_any().downcast_ref::<HomePage>();
+        assert_eq!(home_page.unwrap().label, None);
+
+        // Nothing was changed, so there is nothing to undo
+        assert!(nav.handle_action(Action::Undo).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_handle_blockers() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let blocker_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.add_blocker = Box::new(move || Some(blocker_id));
+        prompts.remove_blocker = Box::new(move || Some(blocker_id));
+        prompts.update_status = Box::new(|_| Some(Status::Resolved));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddBlocker { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories.get(&story_id).unwrap().blocked_by, vec![blocker_id]);
+        assert!(nav.handle_action(Action::AddBlocker { story_id: blocker_id }).is_err());
+
+        // The blocker is still open
+        assert!(nav.handle_action(Action::UpdateStoryStatus { story_id }).is_err());
+
+        nav.handle_action(Action::RemoveBlocker { story_id }).unwrap();
+        nav.handle_action(Action::UpdateStoryStatus { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories.get(&story_id).unwrap().status, Status::Resolved);
+    }
+
+    #[test]
+    fn handle_action_should_handle_comments() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        let mut prompts = Prompts::new();
+        prompts.add_comment = Box::new(|| Some(("looks good".to_owned(), None)));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddComment { item_id: story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let (_, comment_id, comment) = db_state.comments_of(story_id)[0];
+        assert_eq!(comment.body, "looks good".to_owned());
+        assert_eq!(comment.author, current_actor());
+
+        let mut prompts = Prompts::new();
+        prompts.add_comment = Box::new(move || Some(("thanks".to_owned(), Some(comment_id))));
+        prompts.delete_comment = Box::new(move || Some(comment_id));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddComment { item_id: story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().comments_of(story_id)[1].0, 1);
+        assert!(nav.handle_action(Action::AddComment { item_id: 999 }).is_err());
+
+        nav.handle_action(Action::DeleteComment { item_id: story_id }).unwrap();
+        assert!(db.read_db().unwrap().comments.is_empty());
+
+        nav.handle_action(Action::Undo).unwrap();
+        assert_eq!(db.read_db().unwrap().comments.len(), 2);
+    }
+
     #[test]
     fn handle_action_should_handle_delete_story() {
-        let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
         let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
         let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
 
@@ -0,0 +1,430 @@
+use std::io::Read;
+
+use anyhow::anyhow;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::db::{Change, JiraDatabase, NotFound, UndoHistory};
+use crate::models::{DBState, Epic, Status, Story};
+
+/// Serves the board as a JSON API on localhost until the process is stopped. Requests are handled one at a time.
+///
+/// ```text
+/// GET    /epics                  GET    /stories
+/// POST   /epics                  GET    /stories/:id
+/// GET    /epics/:id              PUT    /stories/:id/status
+/// PUT    /epics/:id/status       DELETE /stories/:id
+/// DELETE /epics/:id
+/// GET    /epics/:id/stories      POST   /undo
+/// POST   /epics/:id/stories      POST   /redo
+/// ```
+///
+/// Changes made through the API can be undone and redone in order, for as long as the server runs.
+pub fn serve(db: &JiraDatabase, port: u16) -> anyhow::Result<()> {
+    let server = Server::http(("127.0.0.1", port)).map_err(|error| anyhow!("Unable to listen on port {}: {}", port, error))?;
+    run(&server, db);
+
+    Ok(())
+}
+
+fn run(server: &Server, db: &JiraDatabase) {
+    let mut history = UndoHistory::default();
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let reply = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => handle(db, &mut history, request.method(), request.url(), &body),
+            Err(error) => ApiError::bad_request(anyhow!("Unable to read request body: {}", error)).into(),
+        };
+
+        //A client that went away shouldn't stop the server
+        if let Err(error) = request.respond(reply.into_response()) {
+            eprintln!("Error: Unable to send response: {}", error);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Reply {
+    status: u16,
+    body: Option<Value>,
+}
+
+impl Reply {
+    fn ok(body: Value) -> Self {
+        Reply { status: 200, body: Some(body) }
+    }
+
+    fn created(body: Value) -> Self {
+        Reply { status: 201, body: Some(body) }
+    }
+
+    fn no_content() -> Self {
+        Reply { status: 204, body: None }
+    }
+
+    fn into_response(self) -> Response<std::io::Cursor<Vec<u8>>> {
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("valid header");
+
+        match self.body {
+            Some(body) => Response::from_string(body.to_string()).with_header(content_type).with_status_code(self.status),
+            None => Response::from_data(vec![]).with_status_code(self.status),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn not_found(message: impl Into<String>) -> Self {
+        ApiError { status: 404, message: message.into() }
+    }
+
+    // The request was understood but rejected, e.g. by the workflow
+    fn bad_request(error: anyhow::Error) -> Self {
+        ApiError { status: 400, message: format!("{:#}", error) }
+    }
+
+    // A refused mutation, 404 when it was refused because the epic or story isn't on the board
+    fn rejected(error: anyhow::Error) -> Self {
+        match error.downcast_ref::<NotFound>() {
+            Some(not_found) => ApiError::not_found(not_found.to_string()),
+            None => ApiError::bad_request(error),
+        }
+    }
+}
+
+// Reading the board failed, nothing the client can fix
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        ApiError { status: 500, message: format!("{:#}", error) }
+    }
+}
+
+impl From<ApiError> for Reply {
+    fn from(error: ApiError) -> Self {
+        Reply { status: error.status, body: Some(json!({ "error": error.message })) }
+    }
+}
+
+#[derive(Deserialize)]
+struct NewItem {
+    name: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct StatusUpdate {
+    status: String,
+}
+
+#[derive(Serialize)]
+struct ItemResponse<'a, T> {
+    id: u32,
+    #[serde(flatten)]
+    item: &'a T,
+}
+
+fn handle(db: &JiraDatabase, history: &mut UndoHistory, method: &Method, url: &str, body: &str) -> Reply {
+    route(db, history, method, url, body).unwrap_or_else(Reply::from)
+}
+
+fn route(db: &JiraDatabase, history: &mut UndoHistory, method: &Method, url: &str, body: &str) -> Result<Reply, ApiError> {
+    let path = url.split('?').next().unwrap_or_default();
+    let segments = path.trim_matches('/').split('/').collect::<Vec<&str>>();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["epics"]) => {
+            let db_state = db.read_db()?;
+            Ok(Reply::ok(json!(db_state.epics.keys().sorted().map(|id| epic_json(*id, &db_state.epics[id])).collect::<Vec<Value>>())))
+        }
+        (Method::Post, ["epics"]) => {
+            let new_epic = parse_body::<NewItem>(body)?;
+            let (epic_id, db_state) = mutate(db, history, || db.create_epic(Epic::new(new_epic.name, new_epic.description)))?;
+            Ok(Reply::created(get_epic(&db_state, epic_id)?))
+        }
+        (Method::Get, ["epics", epic_id]) => Ok(Reply::ok(get_epic(&db.read_db()?, parse_id(epic_id)?)?)),
+        (Method::Put, ["epics", epic_id, "status"]) => {
+            let epic_id = parse_id(epic_id)?;
+            let status = parse_status(body)?;
+
+            let (_, db_state) = mutate(db, history, || db.update_epic_status(epic_id, status))?;
+            Ok(Reply::ok(get_epic(&db_state, epic_id)?))
+        }
+        (Method::Delete, ["epics", epic_id]) => {
+            let epic_id = parse_id(epic_id)?;
+
+            mutate(db, history, || db.delete_epic(epic_id))?;
+            Ok(Reply::no_content())
+        }
+        (Method::Get, ["epics", epic_id, "stories"]) => {
+            let epic_id = parse_id(epic_id)?;
+            let db_state = db.read_db()?;
+            let epic = db_state.epics.get(&epic_id).ok_or_else(|| ApiError::not_found(NotFound::epic(epic_id).to_string()))?;
+
+            let stories = epic.stories.iter()
+                .filter_map(|story_id| db_state.stories.get(story_id).map(|story| story_json(*story_id, story)))
+                .collect::<Vec<Value>>();
+            Ok(Reply::ok(json!(stories)))
+        }
+        (Method::Post, ["epics", epic_id, "stories"]) => {
+            let epic_id = parse_id(epic_id)?;
+            let new_story = parse_body::<NewItem>(body)?;
+
+            let (story_id, db_state) = mutate(db, history, || db.create_story(Story::new(new_story.name, new_story.description), epic_id))?;
+            Ok(Reply::created(get_story(&db_state, story_id)?))
+        }
+        (Method::Get, ["stories"]) => {
+            let db_state = db.read_db()?;
+            Ok(Reply::ok(json!(db_state.stories.keys().sorted().map(|id| story_json(*id, &db_state.stories[id])).collect::<Vec<Value>>())))
+        }
+        (Method::Get, ["stories", story_id]) => Ok(Reply::ok(get_story(&db.read_db()?, parse_id(story_id)?)?)),
+        (Method::Put, ["stories", story_id, "status"]) => {
+            let story_id = parse_id(story_id)?;
+            let status = parse_status(body)?;
+
+            let (_, db_state) = mutate(db, history, || db.update_story_status(story_id, status))?;
+            Ok(Reply::ok(get_story(&db_state, story_id)?))
+        }
+        (Method::Delete, ["stories", story_id]) => {
+            let story_id = parse_id(story_id)?;
+
+            //Look up the epic in the same transaction, so the story can't move or go away in between
+            mutate(db, history, || db.transaction(|db_state| {
+                let epic_id = db_state.epic_of_story(story_id)?;
+                db_state.delete_story(epic_id, story_id)
+            }))?;
+            Ok(Reply::no_content())
+        }
+        (Method::Post, ["undo"]) => {
+            history.undo(db).map_err(ApiError::bad_request)?;
+            Ok(Reply::no_content())
+        }
+        (Method::Post, ["redo"]) => {
+            history.redo(db).map_err(ApiError::bad_request)?;
+            Ok(Reply::no_content())
+        }
+        _ => Err(ApiError::not_found(format!("No route for {} /{}", method, segments.join("/")))),
+    }
+}
+
+// Runs a mutation the same way the Navigator does: refused if the board changes before its transaction starts,
+// and recorded so that it can be undone. Returns its result and the board as the mutation left it.
+fn mutate<T>(db: &JiraDatabase, history: &mut UndoHistory, mutation: impl FnOnce() -> anyhow::Result<T>) -> Result<(T, DBState), ApiError> {
+    let before = db.read_db()?;
+    let result = db.based_on(before.clone(), mutation).map_err(ApiError::rejected)?;
+
+    let after = db.read_db()?;
+    history.record(Change::between(&before, &after));
+    Ok((result, after))
+}
+
+fn get_epic(db_state: &DBState, epic_id: u32) -> Result<Value, ApiError> {
+    let epic = db_state.epics.get(&epic_id).ok_or_else(|| ApiError::not_found(NotFound::epic(epic_id).to_string()))?;
+
+    Ok(epic_json(epic_id, epic))
+}
+
+fn get_story(db_state: &DBState, story_id: u32) -> Result<Value, ApiError> {
+    let story = db_state.stories.get(&story_id).ok_or_else(|| ApiError::not_found(NotFound::story(story_id).to_string()))?;
+
+    Ok(story_json(story_id, story))
+}
+
+fn epic_json(id: u32, epic: &Epic) -> Value {
+    json!(ItemResponse { id, item: epic })
+}
+
+fn story_json(id: u32, story: &Story) -> Value {
+    json!(ItemResponse { id, item: story })
+}
+
+fn parse_id(id: &str) -> Result<u32, ApiError> {
+    id.parse::<u32>().map_err(|_| ApiError::bad_request(anyhow!("Invalid id: {}", id)))
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, ApiError> {
+    serde_json::from_str::<T>(body).map_err(|error| ApiError::bad_request(anyhow!("Invalid request body: {}", error)))
+}
+
+fn parse_status(body: &str) -> Result<Status, ApiError> {
+    parse_body::<StatusUpdate>(body)?.status.parse::<Status>().map_err(ApiError::bad_request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    use crate::models::DBState;
+
+    // Starts a server on a free port backed by a json file in `dir` and returns its address
+    fn start_server(dir: &tempfile::TempDir) -> String {
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_owned();
+        JiraDatabase::new(file_path.clone()).database.write_db(&DBState::default()).unwrap();
+
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap().to_string();
+
+        //The server thread owns its database, just like `jiracli serve`
+        std::thread::spawn(move || run(&server, &JiraDatabase::new(file_path)));
+
+        address
+    }
+
+    // Minimal HTTP/1.1 client, returns the status code and the parsed body
+    fn request(address: &str, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", method, path, address, body.len(), body).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        let body = if body.is_empty() { Value::Null } else { serde_json::from_str(body).unwrap() };
+
+        (status, body)
+    }
+
+    #[test]
+    fn epics_should_be_created_listed_updated_and_deleted() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = start_server(&dir);
+
+        let (status, epic) = request(&address, "POST", "/epics", r#"{"name": "Login", "description": "Sign in"}"#);
+        assert_eq!(status, 201);
+        assert_eq!(epic["id"], 1);
+        assert_eq!(epic["name"], "Login");
+        assert_eq!(epic["status"], "Open");
+
+        let (status, epics) = request(&address, "GET", "/epics", "");
+        assert_eq!(status, 200);
+        assert_eq!(epics.as_array().unwrap().len(), 1);
+
+        let (status, epic) = request(&address, "PUT", "/epics/1/status", r#"{"status": "in-progress"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(epic["status"], "InProgress");
+
+        let (status, _) = request(&address, "DELETE", "/epics/1", "");
+        assert_eq!(status, 204);
+
+        let (status, error) = request(&address, "GET", "/epics/1", "");
+        assert_eq!(status, 404);
+        assert_eq!(error["error"], "Epic 1 not found.");
+
+        //Every change went through to the file
+        let db_state = JiraDatabase::new(dir.path().join("db.json").to_str().unwrap().to_owned()).read_db().unwrap();
+        assert!(db_state.epics.is_empty());
+        assert_eq!(db_state.last_item_id, 1);
+    }
+
+    #[test]
+    fn stories_should_be_created_listed_updated_and_deleted() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = start_server(&dir);
+
+        request(&address, "POST", "/epics", r#"{"name": "Login"}"#);
+
+        let (status, story) = request(&address, "POST", "/epics/1/stories", r#"{"name": "Login form"}"#);
+        assert_eq!(status, 201);
+        assert_eq!(story["id"], 2);
+        assert_eq!(story["description"], "");
+
+        let (_, stories) = request(&address, "GET", "/epics/1/stories", "");
+        assert_eq!(stories.as_array().unwrap().len(), 1);
+
+        let (status, story) = request(&address, "PUT", "/stories/2/status", r#"{"status": "resolved"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(story["status"], "Resolved");
+
+        let (status, story) = request(&address, "GET", "/stories/2", "");
+        assert_eq!(status, 200);
+        assert_eq!(story["name"], "Login form");
+
+        let (status, _) = request(&address, "DELETE", "/stories/2", "");
+        assert_eq!(status, 204);
+
+        let (_, stories) = request(&address, "GET", "/stories", "");
+        assert_eq!(stories, json!([]));
+    }
+
+    #[test]
+    fn invalid_requests_should_return_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = start_server(&dir);
+
+        assert_eq!(request(&address, "GET", "/unknown", "").0, 404);
+        assert_eq!(request(&address, "GET", "/epics/abc", "").0, 400);
+        assert_eq!(request(&address, "DELETE", "/stories/abc", "").0, 400);
+        assert_eq!(request(&address, "POST", "/epics", "not json").0, 400);
+        assert_eq!(request(&address, "POST", "/epics/9/stories", r#"{"name": "story"}"#).0, 404);
+
+        request(&address, "POST", "/epics", r#"{"name": "Login"}"#);
+        let (status, error) = request(&address, "PUT", "/epics/1/status", r#"{"status": "done"}"#);
+        assert_eq!(status, 400);
+        assert!(error["error"].as_str().unwrap().starts_with("Invalid status 'done'"));
+    }
+
+    #[test]
+    fn mutations_of_missing_items_should_return_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = start_server(&dir);
+
+        let (status, error) = request(&address, "PUT", "/epics/9/status", r#"{"status": "closed"}"#);
+        assert_eq!(status, 404);
+        assert_eq!(error["error"], "Epic 9 not found.");
+        assert_eq!(request(&address, "DELETE", "/epics/9", "").0, 404);
+
+        let (status, error) = request(&address, "PUT", "/stories/9/status", r#"{"status": "closed"}"#);
+        assert_eq!(status, 404);
+        assert_eq!(error["error"], "Story 9 not found.");
+        let (status, error) = request(&address, "DELETE", "/stories/9", "");
+        assert_eq!(status, 404);
+        assert_eq!(error["error"], "Story 9 not found.");
+    }
+
+    #[test]
+    fn changes_should_be_undone_and_redone() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = start_server(&dir);
+
+        request(&address, "POST", "/epics", r#"{"name": "Login"}"#);
+        request(&address, "PUT", "/epics/1/status", r#"{"status": "closed"}"#);
+
+        assert_eq!(request(&address, "POST", "/undo", "").0, 204);
+        assert_eq!(request(&address, "GET", "/epics/1", "").1["status"], "Open");
+
+        assert_eq!(request(&address, "POST", "/redo", "").0, 204);
+        assert_eq!(request(&address, "GET", "/epics/1", "").1["status"], "Closed");
+        assert_eq!(request(&address, "POST", "/redo", "").0, 400);
+
+        request(&address, "POST", "/undo", "");
+        request(&address, "POST", "/undo", "");
+        assert_eq!(request(&address, "GET", "/epics/1", "").0, 404);
+
+        let (status, error) = request(&address, "POST", "/undo", "");
+        assert_eq!(status, 400);
+        assert_eq!(error["error"], "Nothing to undo.");
+    }
+
+    #[test]
+    fn status_updates_should_follow_the_workflow() {
+        let workflow = crate::workflow::Workflow::parse("statuses = [\"Open\", \"Closed\"]\n[transitions]").unwrap();
+        let db = JiraDatabase::with_database(Box::new(crate::db::test_utils::MockDB::new())).with_workflow(workflow);
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let reply = handle(&db, &mut UndoHistory::default(), &Method::Put, &format!("/epics/{}/status", epic_id), r#"{"status": "closed"}"#);
+
+        assert_eq!(reply.status, 400);
+        assert_eq!(reply.body.unwrap()["error"], "Status OPEN can't change to CLOSED in this workflow.");
+    }
+}
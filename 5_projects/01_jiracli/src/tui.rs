@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::models::Action;
+use crate::navigator::Navigator;
+use crate::ui::PageView;
+
+// Rows moved by page up / page down
+const PAGE_SIZE: usize = 10;
+
+// How often to check for changes to the board while waiting for a key
+const BOARD_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Full screen alternative to the line based loop in main. Pages, actions and prompts are shared with it.
+/// The page is redrawn whenever `board_changed` returns true.
+pub fn run(nav: &mut Navigator, board_changed: impl Fn() -> bool) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, nav, board_changed);
+    ratatui::restore();
+
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, nav: &mut Navigator, board_changed: impl Fn() -> bool) -> Result<()> {
+    // One selection per page on the stack, so going back keeps the previous position
+    let mut selections: Vec<TableState> = vec![];
+    let mut status = String::new();
+
+    while let Some(page) = nav.get_current_page() {
+        selections.resize_with(nav.get_page_count(), || TableState::default().with_selected(Some(0)));
+        let selection = selections.last_mut().unwrap();
+
+        let view = page.view().unwrap_or_else(|error| PageView { title: "ERROR".to_owned(), details: vec![format!("{:#}", error)], keys: vec![("p", "previous")], ..Default::default() });
+        terminal.draw(|frame| draw(frame, nav.board_name(), &view, selection, &status))?;
+
+        let Some(event) = next_event(&board_changed)? else {
+            if let Err(error) = nav.refresh() {
+                status = format!("Error: {:#}", error);
+            }
+            continue;
+        };
+
+        let Event::Key(key) = event else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        let action = match input_for_key(key, &view, selection) {
+            Some(KeyInput::Page(input)) => page.handle_input(&input),
+            Some(KeyInput::Back) => Ok(Some(Action::NavigateToPreviousPage)),
+            None => Ok(None),
+        };
+
+        status.clear();
+        let result = match action {
+            // Prompts read from stdin line by line, so leave the full screen while they run
+            Ok(Some(action)) if action.is_mutation() || matches!(action, Action::Search | Action::FilterByLabel) => {
+                ratatui::restore();
+                let result = nav.handle_action(action);
+                *terminal = ratatui::init();
+                result
+            }
+            Ok(Some(action)) => nav.handle_action(action),
+            Ok(None) => Ok(()),
+            Err(error) => Err(error),
+        };
+
+        if let Err(error) = result {
+            status = format!("Error: {:#}", error);
+        }
+    }
+
+    Ok(())
+}
+
+// Waits for the next terminal event, or returns None when the board changed and the page has to be redrawn.
+fn next_event(board_changed: impl Fn() -> bool) -> Result<Option<Event>> {
+    loop {
+        if event::poll(BOARD_CHECK_INTERVAL)? {
+            return Ok(Some(event::read()?));
+        }
+        if board_changed() {
+            return Ok(None);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum KeyInput {
+    // Input for `Page::handle_input`
+    Page(String),
+    Back,
+}
+
+// Moves the selection for navigation keys and returns the input for every other key.
+fn input_for_key(key: KeyEvent, view: &PageView, selection: &mut TableState) -> Option<KeyInput> {
+    let last_row = view.table.rows.len().saturating_sub(1);
+    let selected = selection.selected().unwrap_or(0).min(last_row);
+
+    match key.code {
+        KeyCode::Up => selection.select(Some(selected.saturating_sub(1))),
+        KeyCode::Down => selection.select(Some((selected + 1).min(last_row))),
+        KeyCode::PageUp => selection.select(Some(selected.saturating_sub(PAGE_SIZE))),
+        KeyCode::PageDown => selection.select(Some((selected + PAGE_SIZE).min(last_row))),
+        KeyCode::Home => selection.select(Some(0)),
+        KeyCode::End => selection.select(Some(last_row)),
+        KeyCode::Enter => return view.table.rows.get(selected).and_then(|row| row.input.clone()).map(KeyInput::Page),
+        KeyCode::Esc | KeyCode::Backspace => return Some(KeyInput::Back),
+        KeyCode::Char(c) => return Some(KeyInput::Page(c.to_string())),
+        _ => {}
+    }
+
+    None
+}
+
+fn draw(frame: &mut Frame, board_name: &str, view: &PageView, selection: &mut TableState, status: &str) {
+    let [details_area, table_area, keys_area, status_area] = Layout::vertical([
+        Constraint::Length(view.details.len() as u16 + 2),
+        Constraint::Min(3),
+        Constraint::Length(3),
+        Constraint::Length(1),
+    ]).areas(frame.area());
+
+    let details = Paragraph::new(view.details.iter().map(|line| Line::from(line.as_str())).collect::<Vec<Line>>())
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} | {} ", board_name, view.title)))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(details, details_area);
+
+    let header = Row::new(view.table.headers.clone()).style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = view.table.rows.iter().map(|row| Row::new(row.cells.clone()));
+    let table = Table::new(rows, view.table.widths.iter().map(|width| Constraint::Length(*width)))
+        .header(header)
+        .block(Block::default().borders(Borders::ALL))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(table, table_area, selection);
+
+    let keys = view.keys.iter()
+        .map(|(key, description)| format!("[{}] {}", key, description))
+        .chain(["[↑/↓] select".to_owned()])
+        .chain(view.table.open.map(|open| format!("[enter] {}", open)))
+        .chain(["[esc] back".to_owned()])
+        .collect::<Vec<String>>()
+        .join(" | ");
+    frame.render_widget(Paragraph::new(keys).wrap(Wrap { trim: true }).block(Block::default().borders(Borders::TOP)), keys_area);
+
+    frame.render_widget(Paragraph::new(status), status_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{TableRow, TableView};
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyModifiers;
+    use ratatui::Terminal;
+
+    fn sample_view() -> PageView {
+        PageView {
+            title: "EPICS".to_owned(),
+            details: vec!["some details".to_owned()],
+            table: TableView {
+                headers: vec!["id", "name"],
+                widths: vec![4, 20],
+                rows: (1..=20).map(|id| TableRow::new(Some(id.to_string()), vec![id.to_string(), format!("epic {}", id)])).collect(),
+                open: Some("navigate to epic"),
+            },
+            keys: vec![("q", "quit")],
+        }
+    }
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn input_for_key_should_move_selection() {
+        let view = sample_view();
+        let mut selection = TableState::default().with_selected(Some(0));
+
+        assert_eq!(input_for_key(press(KeyCode::Up), &view, &mut selection), None);
+        assert_eq!(selection.selected(), Some(0));
+
+        input_for_key(press(KeyCode::Down), &view, &mut selection);
+        assert_eq!(selection.selected(), Some(1));
+
+        input_for_key(press(KeyCode::PageDown), &view, &mut selection);
+        assert_eq!(selection.selected(), Some(11));
+
+        input_for_key(press(KeyCode::PageDown), &view, &mut selection);
+        assert_eq!(selection.selected(), Some(19));
+
+        input_for_key(press(KeyCode::Home), &view, &mut selection);
+        assert_eq!(selection.selected(), Some(0));
+    }
+
+    #[test]
+    fn input_for_key_should_return_page_input() {
+        let view = sample_view();
+        let mut selection = TableState::default().with_selected(Some(2));
+
+        assert_eq!(input_for_key(press(KeyCode::Enter), &view, &mut selection), Some(KeyInput::Page("3".to_owned())));
+        assert_eq!(input_for_key(press(KeyCode::Char('q')), &view, &mut selection), Some(KeyInput::Page("q".to_owned())));
+        assert_eq!(input_for_key(press(KeyCode::Esc), &view, &mut selection), Some(KeyInput::Back));
+
+        let empty_view = PageView::default();
+        assert_eq!(input_for_key(press(KeyCode::Enter), &empty_view, &mut selection), None);
+    }
+
+    #[test]
+    fn draw_should_render_view_and_status() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut selection = TableState::default().with_selected(Some(0));
+
+        terminal.draw(|frame| draw(frame, "work", &sample_view(), &mut selection, "Error: boom")).unwrap();
+
+        let screen = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect::<String>();
+        assert!(screen.contains("work | EPICS"));
+        assert!(screen.contains("some details"));
+        assert!(screen.contains("> 1"));
+        assert!(screen.contains("[q] quit"));
+        assert!(screen.contains("Error: boom"));
+    }
+}
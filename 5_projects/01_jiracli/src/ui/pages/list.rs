@@ -0,0 +1,192 @@
+use std::cell::Cell;
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{Epic, Status, Story};
+
+// Rows shown per page of a list
+pub const PAGE_SIZE: usize = 20;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Id,
+    Name,
+    // In board order of the workflow
+    Status,
+    // Most recently updated first
+    Updated,
+}
+
+impl SortOrder {
+    fn next(self) -> Self {
+        match self {
+            SortOrder::Id => SortOrder::Name,
+            SortOrder::Name => SortOrder::Status,
+            SortOrder::Status => SortOrder::Updated,
+            SortOrder::Updated => SortOrder::Id,
+        }
+    }
+}
+
+impl Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortOrder::Id => write!(f, "id"),
+            SortOrder::Name => write!(f, "name"),
+            SortOrder::Status => write!(f, "status"),
+            SortOrder::Updated => write!(f, "updated"),
+        }
+    }
+}
+
+/// What the list pages can be sorted by.
+pub trait Listed {
+    fn name(&self) -> &str;
+    fn status(&self) -> &Status;
+    fn updated_at(&self) -> Option<DateTime<Utc>>;
+}
+
+impl Listed for Epic {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &Status {
+        &self.status
+    }
+
+    fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+}
+
+impl Listed for Story {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &Status {
+        &self.status
+    }
+
+    fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+}
+
+/// The page and sort order of a list page. Pages only get `&self`, so both are kept in cells.
+#[derive(Debug, Default)]
+pub struct ListState {
+    page: Cell<usize>,
+    sort: Cell<SortOrder>,
+}
+
+impl ListState {
+    pub fn sort(&self) -> SortOrder {
+        self.sort.get()
+    }
+
+    // Moving past the last page is undone by `arrange`, which knows how many items there are
+    pub fn next_page(&self) {
+        self.page.set(self.page.get() + 1);
+    }
+
+    pub fn previous_page(&self) {
+        self.page.set(self.page.get().saturating_sub(1));
+    }
+
+    /// Switches to the next sort order, starting over at the first page.
+    pub fn next_sort(&self) {
+        self.sort.set(self.sort.get().next());
+        self.page.set(0);
+    }
+
+    /// Sorts the items and returns those on the current page. Ties are sorted by id.
+    pub fn arrange<'a, T: Listed>(&self, mut items: Vec<(u32, &'a T)>, statuses: &[Status]) -> Vec<(u32, &'a T)> {
+        items.sort_by_key(|(id, _)| *id);
+        match self.sort.get() {
+            SortOrder::Id => {}
+            SortOrder::Name => items.sort_by_cached_key(|(_, item)| item.name().to_lowercase()),
+            SortOrder::Status => items.sort_by_key(|(_, item)| statuses.iter().position(|status| status == item.status()).unwrap_or(statuses.len())),
+            SortOrder::Updated => items.sort_by_key(|(_, item)| std::cmp::Reverse(item.updated_at())),
+        }
+
+        let last_page = items.len().saturating_sub(1) / PAGE_SIZE;
+        self.page.set(self.page.get().min(last_page));
+
+        items.into_iter().skip(self.page.get() * PAGE_SIZE).take(PAGE_SIZE).collect()
+    }
+
+    /// e.g. "page 2/3 | sort: name", or None while everything fits on one page in id order.
+    pub fn footer(&self, total: usize) -> Option<String> {
+        let pages = total.div_ceil(PAGE_SIZE).max(1);
+        if pages == 1 && self.sort.get() == SortOrder::default() {
+            return None;
+        }
+
+        Some(format!("page {}/{} | sort: {}", self.page.get() + 1, pages, self.sort.get()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn stories(count: usize) -> Vec<Story> {
+        (0..count).map(|index| Story::new(format!("story {:02}", count - index), "".to_owned())).collect()
+    }
+
+    fn ids<T>(items: &[(u32, &T)]) -> Vec<u32> {
+        items.iter().map(|(id, _)| *id).collect()
+    }
+
+    #[test]
+    fn arrange_should_page_and_clamp() {
+        let stories = stories(45);
+        let items = || stories.iter().enumerate().map(|(index, story)| (index as u32, story)).collect::<Vec<_>>();
+        let list = ListState::default();
+
+        assert_eq!(list.arrange(items(), &Status::ALL).len(), PAGE_SIZE);
+        assert_eq!(list.footer(45), Some("page 1/3 | sort: id".to_owned()));
+
+        list.next_page();
+        list.next_page();
+        list.next_page();
+        assert_eq!(ids(&list.arrange(items(), &Status::ALL)), (40..45).collect::<Vec<u32>>());
+        assert_eq!(list.footer(45), Some("page 3/3 | sort: id".to_owned()));
+
+        list.previous_page();
+        assert_eq!(ids(&list.arrange(items(), &Status::ALL))[0], 20);
+
+        assert_eq!(ListState::default().footer(5), None);
+    }
+
+    #[test]
+    fn arrange_should_sort() {
+        let mut stories = stories(3);
+        stories[0].status = Status::Closed;
+        stories[1].status = Status::InProgress;
+        stories[2].updated_at = stories[2].updated_at.map(|updated_at| updated_at - Duration::days(1));
+        let items = || stories.iter().enumerate().map(|(index, story)| (index as u32, story)).collect::<Vec<_>>();
+
+        let list = ListState::default();
+        assert_eq!(ids(&list.arrange(items(), &Status::ALL)), vec![0, 1, 2]);
+
+        list.next_sort();
+        assert_eq!(list.sort(), SortOrder::Name);
+        assert_eq!(ids(&list.arrange(items(), &Status::ALL)), vec![2, 1, 0]);
+
+        list.next_sort();
+        assert_eq!(ids(&list.arrange(items(), &Status::ALL)), vec![2, 1, 0]);
+
+        list.next_sort();
+        assert_eq!(ids(&list.arrange(items(), &Status::ALL))[2], 2);
+        assert_eq!(list.footer(3), Some("page 1/1 | sort: updated".to_owned()));
+
+        list.next_sort();
+        assert_eq!(list.sort(), SortOrder::Id);
+    }
+}
@@ -5,58 +5,70 @@ use itertools::Itertools;
 use anyhow::Result;
 use anyhow::anyhow;
 
-use crate::db::JiraDatabase;
-use crate::models::Action;
+use crate::db::{JiraDatabase, ItemType};
+use crate::models::{Action, DBState, Epic, Status, Story};
 
 mod page_helpers;
 // use page_helpers::*;
 
+mod view;
+pub use view::*;
+
+mod list;
+pub use list::{ListState, SortOrder};
+
 pub trait Page {
-    fn draw_page(&self) -> Result<()>;
+    // Prints the page for the line based loop in main. Pages only describe what they show in `view`.
+    fn draw_page(&self) -> Result<()> {
+        println!("{}", self.view()?.to_text());
+        Ok(())
+    }
     fn handle_input(&self, input: &str) -> Result<Option<Action>>;
+    fn view(&self) -> Result<PageView>;
     fn as_any(&self) -> &dyn Any;
 }
 
 pub struct HomePage {
-    pub db: Rc<JiraDatabase>
+    pub db: Rc<JiraDatabase>,
+    // Only list epics that carry this label or have a story that does
+    pub label: Option<String>,
+    pub list: ListState,
 }
 
-impl Page for HomePage {
-    fn draw_page(&self) -> Result<()> {
-        println!("----------------------------- EPICS -----------------------------");
-        println!("     id     |               name               |      status      ");
-        
-        //Read in the database from disk.
-        let db_state = self.db.read_db()?;
-
-        //Set the col width
-        let width_col_id = 12;
-        let width_col_name = 33;
-        let width_col_status = 17;
-
-        //Loop through the sorted epic list
-        for epic_key in db_state.epics.keys().sorted(){
-            let curr_epic = db_state.epics.get(epic_key).ok_or_else(|| anyhow!("could not find epic!"))?;
-
-            let eid = page_helpers::get_column_string(&epic_key.to_string(), width_col_id);
-            let ename = page_helpers::get_column_string(&curr_epic.name, width_col_name);
-            let estate = page_helpers::get_column_string(format!("{}", curr_epic.status).as_str(), width_col_status);
-
-            println!("{:width_col_id$}| {:width_col_name$}| {:width_col_status$}", eid, ename, estate);
-        }
+impl HomePage {
+    fn epic_ids(&self, db_state: &DBState) -> Vec<u32> {
+        db_state.epics.keys()
+            .filter(|epic_id| self.label.as_ref().is_none_or(|label| db_state.epic_has_label(**epic_id, label)))
+            .sorted()
+            .copied()
+            .collect()
+    }
 
-        println!();
-        println!();
-        println!("[q] quit | [c] create epic | [:id:] navigate to epic");
+    // The epics on the current page, in the selected order, and the footer telling where we are
+    fn listed_epic_ids(&self, db_state: &DBState) -> (Vec<u32>, Option<String>) {
+        let epic_ids = self.epic_ids(db_state);
+        let epics = epic_ids.iter().map(|epic_id| (*epic_id, &db_state.epics[epic_id])).collect();
+        let listed = self.list.arrange(epics, self.db.workflow.statuses());
 
-        Ok(())
+        (listed.into_iter().map(|(epic_id, _)| epic_id).collect(), self.list.footer(epic_ids.len()))
     }
+}
 
+impl Page for HomePage {
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {        
         // Match against the user input and return the corresponding action. If the user input was invalid return None.
         match input{
             "q" => Ok(Some(Action::Exit)),
             "c" => Ok(Some(Action::CreateEpic)),
+            "s" => Ok(Some(Action::Search)),
+            "v" => Ok(Some(Action::NavigateToBoard { epic_id: None })),
+            "l" => Ok(Some(Action::FilterByLabel)),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            //Paging and sorting only change this page, there is nothing for the navigator to do
+            "n" => { self.list.next_page(); Ok(None) }
+            "b" => { self.list.previous_page(); Ok(None) }
+            "o" => { self.list.next_sort(); Ok(None) }
             input if input.parse::<u32>().is_ok() => {
                 //Read in the database from disk.
                 let db_state = self.db.read_db()?;
@@ -73,6 +85,26 @@ impl Page for HomePage {
         }
     }
     
+    fn view(&self) -> Result<PageView> {
+        let db_state = self.db.read_db()?;
+
+        let (epic_ids, footer) = self.listed_epic_ids(&db_state);
+
+        let mut rows = vec![];
+        for epic_id in epic_ids {
+            let epic = &db_state.epics[&epic_id];
+            let progress = page_helpers::get_progress_string(&db_state.epic_progress(epic_id)?);
+            rows.push(TableRow::new(Some(epic_id.to_string()), vec![epic_id.to_string(), epic.name.clone(), epic.status.to_string(), progress, epic.labels.join(", ")]));
+        }
+
+        Ok(PageView {
+            title: "EPICS".to_owned(),
+            details: self.label.iter().map(|label| format!("label: {}", label)).chain(footer).collect(),
+            table: TableView { headers: vec!["id", "name", "status", "progress", "labels"], widths: vec![10, 40, 14, 16, 20], rows, open: Some("navigate to epic") },
+            keys: vec![("q", "quit"), ("c", "create epic"), ("s", "search"), ("v", "board"), ("l", "filter by label"), ("z", "undo"), ("y", "redo"), ("n", "next page"), ("b", "previous page"), ("o", "sort")],
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -80,68 +112,46 @@ impl Page for HomePage {
 
 pub struct EpicDetail {
     pub epic_id: u32,
-    pub db: Rc<JiraDatabase>
+    pub db: Rc<JiraDatabase>,
+    pub list: ListState,
 }
 
-impl Page for EpicDetail {
-    fn draw_page(&self) -> Result<()> {
-        
-        //Set the col width
-        let width_col_eid = 6;
-        let width_col_ename = 13;
-        let width_col_edesc = 28;
-        let width_col_estatus = 13;
-        let width_col_sid = 12;
-        let width_col_sname = 33;
-        let width_col_sstatus = 17;
-
-        let db_state = self.db.read_db()?;
-        let epic = db_state.epics.get(&self.epic_id).ok_or_else(|| anyhow!("Could not find epic!"))?;
-
-        println!("------------------------------ EPIC ------------------------------");
-        println!("  id  |     name     |         description         |    status    ");
-
-        // Print out epic details using get_column_string()
-        let eid = page_helpers::get_column_string(&self.epic_id.to_string(), width_col_eid);
-        let ename = page_helpers::get_column_string(&epic.name, width_col_ename);
-        let edesc = page_helpers::get_column_string(&epic.description, width_col_edesc);
-        let estatus = page_helpers::get_column_string(format!("{}", epic.status).as_str(), width_col_estatus);
-  
-        println!("{:width_col_eid$}| {:width_col_ename$}| {:width_col_edesc$}| {:width_col_estatus$}", eid, ename, edesc, estatus);
-
-        println!();
-        println!();
-        println!("---------------------------- STORIES ----------------------------");
-        println!("     id     |               name               |      status      ");
-
-        let stories = &db_state.stories;
-        
-        //Loop through all the sorted stories and print out
-        for story_id in &epic.stories{
-            let curr_story = db_state.stories.get(story_id).ok_or_else(||println!("Invalid Story ID in Epic: {}", story_id)).unwrap();
-
-            let sid = page_helpers::get_column_string(&story_id.to_string(), width_col_sid);
-            let sname = page_helpers::get_column_string(&curr_story.name, width_col_sname);
-            let sstatus = page_helpers::get_column_string(format!("{}", curr_story.status).as_str(), width_col_sstatus);
-
-            println!("{:width_col_sid$}| {:width_col_sname$}| {:width_col_sstatus$}", sid, sname, sstatus);
-        }
-
-        println!();
-        println!();
-        println!("[p] previous | [u] update epic | [d] delete epic | [c] create story | [:id:] navigate to story\n\n");
-
-        Ok(())
+impl EpicDetail {
+    // The stories on the current page in the selected order. Ask `self.list` for the footer afterwards.
+    fn listed_stories<'a>(&self, db_state: &'a DBState, epic: &Epic) -> Result<Vec<(u32, &'a Story)>> {
+        let stories = epic.stories.iter()
+            .map(|story_id| {
+                let story = db_state.stories.get(story_id).ok_or_else(|| anyhow!("Invalid Story ID in Epic: {}. Run `jiracli doctor` to check the database.", story_id))?;
+                Ok((*story_id, story))
+            })
+            .collect::<Result<Vec<(u32, &Story)>>>()?;
+
+        Ok(self.list.arrange(stories, self.db.workflow.statuses()))
     }
+}
 
+impl Page for EpicDetail {
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {
         
         // Match against the user input and return the corresponding action. If the user input was invalid return None.        
         match input{
             "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "e" => Ok(Some(Action::UpdateEpic { epic_id: self.epic_id})),
             "u" => Ok(Some(Action::UpdateEpicStatus { epic_id: self.epic_id})),
+            "a" => Ok(Some(Action::UpdateEpicAssignee { epic_id: self.epic_id})),
+            "r" => Ok(Some(Action::UpdateEpicPriority { epic_id: self.epic_id})),
             "d" => Ok(Some(Action::DeleteEpic { epic_id: self.epic_id})),
             "c" => Ok(Some(Action::CreateStory { epic_id: self.epic_id})),
+            "v" => Ok(Some(Action::NavigateToBoard { epic_id: Some(self.epic_id) })),
+            "l" => Ok(Some(Action::AddEpicLabel { epic_id: self.epic_id })),
+            "L" => Ok(Some(Action::RemoveEpicLabel { epic_id: self.epic_id })),
+            "k" => Ok(Some(Action::AddComment { item_id: self.epic_id })),
+            "x" => Ok(Some(Action::DeleteComment { item_id: self.epic_id })),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            "n" => { self.list.next_page(); Ok(None) }
+            "b" => { self.list.previous_page(); Ok(None) }
+            "o" => { self.list.next_sort(); Ok(None) }
             input if input.parse::<u32>().is_ok() => {
                 // Read in the database from disk.
                 let db_state = self.db.read_db()?;
@@ -161,6 +171,49 @@ impl Page for EpicDetail {
         }
     }
 
+    fn view(&self) -> Result<PageView> {
+        let db_state = self.db.read_db()?;
+        let epic = db_state.epics.get(&self.epic_id).ok_or_else(|| anyhow!("Could not find epic!"))?;
+
+        let mut details = vec![
+            epic.description.clone(),
+            format!("status: {} | assignee: {} | priority: {}", epic.status, page_helpers::get_optional_string(&epic.assignee), epic.priority),
+            format!("created: {} | updated: {}", page_helpers::get_timestamp_string(&epic.created_at), page_helpers::get_timestamp_string(&epic.updated_at)),
+            format!("progress: {} | labels: {}", db_state.epic_progress(self.epic_id)?, page_helpers::get_labels_string(&epic.labels)),
+        ];
+
+        let comments = db_state.comments_of(self.epic_id);
+        if !comments.is_empty() {
+            details.push(String::new());
+            details.extend(comments.into_iter().map(|(depth, comment_id, comment)| page_helpers::get_comment_row(depth, comment_id, comment)));
+        }
+
+        details.push(String::new());
+        details.extend(self.db.history(ItemType::Epic, self.epic_id)?.iter().rev().take(3).rev().map(page_helpers::get_history_row));
+
+        let stories = self.listed_stories(&db_state, epic)?;
+        details.extend(self.list.footer(epic.stories.len()));
+
+        let mut rows = vec![];
+        for (story_id, story) in stories {
+            rows.push(TableRow::new(Some(story_id.to_string()), vec![
+                story_id.to_string(),
+                story.name.clone(),
+                story.status.to_string(),
+                story.priority.to_string(),
+                page_helpers::get_optional_string(&story.story_points),
+                story.labels.join(", "),
+            ]));
+        }
+
+        Ok(PageView {
+            title: format!("EPIC {}: {}", self.epic_id, epic.name),
+            details,
+            table: TableView { headers: vec!["id", "name", "status", "priority", "points", "labels"], widths: vec![8, 36, 14, 10, 6, 20], rows, open: Some("navigate to story") },
+            keys: vec![("p", "previous"), ("e", "edit epic"), ("u", "update epic"), ("a", "assign epic"), ("r", "epic priority"), ("d", "delete epic"), ("c", "create story"), ("v", "board"), ("l", "add label"), ("L", "remove label"), ("k", "comment"), ("x", "delete comment"), ("z", "undo"), ("y", "redo"), ("n", "next page"), ("b", "previous page"), ("o", "sort")],
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -173,44 +226,205 @@ pub struct StoryDetail {
 }
 
 impl Page for StoryDetail {
-    fn draw_page(&self) -> Result<()> {
-        //Set the col width
-        let width_col_id = 6;
-        let width_col_name = 13;
-        let width_col_desc = 28;
-        let width_col_status = 13;
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        // Match against the user input and return the corresponding action. If the user input was invalid return None.
+        match input{
+            "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "e" => Ok(Some(Action::UpdateStory { story_id: (self.story_id) })),
+            "u" => Ok(Some(Action::UpdateStoryStatus { story_id: (self.story_id) })),
+            "a" => Ok(Some(Action::UpdateStoryAssignee { story_id: (self.story_id) })),
+            "r" => Ok(Some(Action::UpdateStoryPriority { story_id: (self.story_id) })),
+            "s" => Ok(Some(Action::UpdateStoryPoints { story_id: (self.story_id) })),
+            "m" => Ok(Some(Action::MoveStory { epic_id: (self.epic_id), story_id: (self.story_id) })),
+            "d" => Ok(Some(Action::DeleteStory { epic_id: (self.epic_id), story_id: (self.story_id) })),
+            "l" => Ok(Some(Action::AddStoryLabel { story_id: (self.story_id) })),
+            "L" => Ok(Some(Action::RemoveStoryLabel { story_id: (self.story_id) })),
+            "b" => Ok(Some(Action::AddBlocker { story_id: (self.story_id) })),
+            "B" => Ok(Some(Action::RemoveBlocker { story_id: (self.story_id) })),
+            "k" => Ok(Some(Action::AddComment { item_id: (self.story_id) })),
+            "x" => Ok(Some(Action::DeleteComment { item_id: (self.story_id) })),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            _ => Ok(None),
+        }
+    }
 
+    fn view(&self) -> Result<PageView> {
         let db_state = self.db.read_db()?;
         let story = db_state.stories.get(&self.story_id).ok_or_else(|| anyhow!("could not find story!"))?;
 
-        println!("------------------------------ STORY ------------------------------");
-        println!("  id  |     name     |         description         |    status    ");
-        
-        // Print out story details using get_column_string()
-        let sid = page_helpers::get_column_string(&self.story_id.to_string(), width_col_id);
-        let sname = page_helpers::get_column_string(&story.name, width_col_name);
-        let sdesc = page_helpers::get_column_string(&story.description, width_col_desc);
-        let sstatus = page_helpers::get_column_string(format!("{}", story.status).as_str(), width_col_status);
-  
-        println!("{:width_col_id$}| {:width_col_name$}| {:width_col_desc$}| {:width_col_status$}", sid, sname, sdesc, sstatus);
-        
-        println!();
-        println!();
-        println!("[p] previous | [u] update story | [d] delete story");
+        let mut details = vec![
+            story.description.clone(),
+            format!("status: {} | assignee: {} | priority: {} | story points: {}", story.status, page_helpers::get_optional_string(&story.assignee), story.priority, page_helpers::get_optional_string(&story.story_points)),
+            format!("created: {} | updated: {}", page_helpers::get_timestamp_string(&story.created_at), page_helpers::get_timestamp_string(&story.updated_at)),
+            format!("labels: {}", page_helpers::get_labels_string(&story.labels)),
+            format!("blocked by: {} | blocks: {}", page_helpers::get_story_links_string(&db_state, &story.blocked_by), page_helpers::get_story_links_string(&db_state, &db_state.blocks(self.story_id))),
+        ];
+
+        let comments = db_state.comments_of(self.story_id);
+        if !comments.is_empty() {
+            details.push(String::new());
+            details.extend(comments.into_iter().map(|(depth, comment_id, comment)| page_helpers::get_comment_row(depth, comment_id, comment)));
+        }
 
-        Ok(())
+        // The story has no children, so its table is the history, newest first
+        let rows = self.db.history(ItemType::Story, self.story_id)?.iter().rev()
+            .map(|event| TableRow::new(None, vec![page_helpers::get_timestamp_string(&Some(event.timestamp)), event.actor.clone(), event.kind.to_string()]))
+            .collect();
+
+        Ok(PageView {
+            title: format!("STORY {}: {}", self.story_id, story.name),
+            details,
+            table: TableView { headers: vec!["when", "who", "change"], widths: vec![17, 12, 50], rows, open: None },
+            keys: vec![("p", "previous"), ("e", "edit story"), ("u", "update story"), ("a", "assign story"), ("r", "story priority"), ("s", "story points"), ("m", "move story"), ("d", "delete story"), ("l", "add label"), ("L", "remove label"), ("k", "comment"), ("x", "delete comment"), ("b", "add blocker"), ("B", "remove blocker"), ("z", "undo"), ("y", "redo")],
+        })
     }
 
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct SearchPage {
+    pub query: String,
+    pub db: Rc<JiraDatabase>
+}
+
+impl Page for SearchPage {
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {
         // Match against the user input and return the corresponding action. If the user input was invalid return None.
         match input{
             "p" => Ok(Some(Action::NavigateToPreviousPage)),
-            "u" => Ok(Some(Action::UpdateStoryStatus { story_id: (self.story_id) })),
-            "d" => Ok(Some(Action::DeleteStory { epic_id: (self.epic_id), story_id: (self.story_id) })),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            input if input.parse::<u32>().is_ok() => {
+                // Only ids that are part of the results can be navigated to
+                let id = input.parse::<u32>().unwrap();
+                let hit = self.db.search(&self.query)?.into_iter().find(|hit| hit.id == id);
+
+                match hit {
+                    Some(hit) if hit.item_type == ItemType::Epic => Ok(Some(Action::NavigateToEpicDetail { epic_id: hit.epic_id })),
+                    Some(hit) => Ok(Some(Action::NavigateToStoryDetail { epic_id: hit.epic_id, story_id: hit.id })),
+                    None => Ok(None),
+                }
+            },
+            _ => Ok(None),
+        }
+    }
+
+    fn view(&self) -> Result<PageView> {
+        let hits = self.db.search(&self.query)?;
+        let db_state = self.db.read_db()?;
+
+        let mut rows = vec![];
+        for hit in &hits {
+            let (item_type, name, status) = match hit.item_type {
+                ItemType::Epic => {
+                    let epic = db_state.epics.get(&hit.id).ok_or_else(|| anyhow!("could not find epic!"))?;
+                    ("epic", &epic.name, &epic.status)
+                }
+                ItemType::Story => {
+                    let story = db_state.stories.get(&hit.id).ok_or_else(|| anyhow!("could not find story!"))?;
+                    ("story", &story.name, &story.status)
+                }
+            };
+            rows.push(TableRow::new(Some(hit.id.to_string()), vec![hit.id.to_string(), item_type.to_owned(), name.clone(), status.to_string()]));
+        }
+
+        Ok(PageView {
+            title: "SEARCH".to_owned(),
+            details: vec![format!("query: {}", self.query), format!("{} result(s)", hits.len())],
+            table: TableView { headers: vec!["id", "type", "name", "status"], widths: vec![8, 7, 36, 14], rows, open: Some("navigate to epic or story") },
+            keys: vec![("p", "previous"), ("z", "undo"), ("y", "redo")],
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// A status and the (story id, story) cards in it
+type BoardColumn<'a> = (Status, Vec<(u32, &'a Story)>);
+
+pub struct BoardPage {
+    // Only the stories of this epic, or every story when None
+    pub epic_id: Option<u32>,
+    pub db: Rc<JiraDatabase>
+}
+
+impl BoardPage {
+    // The stories on the board grouped into one column per workflow status, each sorted by id
+    fn columns<'a>(&self, db_state: &'a DBState) -> Result<Vec<BoardColumn<'a>>> {
+        let story_ids = match self.epic_id {
+            Some(epic_id) => db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Could not find epic!"))?.stories.clone(),
+            None => db_state.epics.values().flat_map(|epic| epic.stories.iter().copied()).collect(),
+        };
+
+        let stories = story_ids.into_iter()
+            .sorted()
+            .filter_map(|story_id| db_state.stories.get(&story_id).map(|story| (story_id, story)))
+            .collect::<Vec<(u32, &Story)>>();
+
+        Ok(self.db.workflow.statuses().iter().cloned()
+            .map(|status| {
+                let cards = stories.iter().filter(|(_, story)| story.status == status).copied().collect();
+                (status, cards)
+            })
+            .collect())
+    }
+
+    fn get_card_string(story_id: u32, story: &Story) -> String {
+        format!("{}: {}", story_id, story.name)
+    }
+}
+
+impl Page for BoardPage {
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        // Match against the user input and return the corresponding action. If the user input was invalid return None.
+        match input{
+            "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            input if input.parse::<u32>().is_ok() => {
+                // Only stories on the board can be moved
+                let story_id = input.parse::<u32>().unwrap();
+                let db_state = self.db.read_db()?;
+                let on_board = self.columns(&db_state)?.iter().any(|(_, cards)| cards.iter().any(|(id, _)| *id == story_id));
+
+                if on_board {
+                    Ok(Some(Action::AdvanceStoryStatus { story_id }))
+                }
+                else {
+                    Ok(None)
+                }
+            },
             _ => Ok(None),
         }
     }
 
+    fn view(&self) -> Result<PageView> {
+        let db_state = self.db.read_db()?;
+        let columns = self.columns(&db_state)?;
+
+        // The full screen UI selects whole rows, so the cards are listed column after column
+        let rows = columns.iter()
+            .flat_map(|(status, cards)| cards.iter().map(move |(story_id, story)| {
+                TableRow::new(Some(story_id.to_string()), vec![status.to_string(), story_id.to_string(), story.name.clone()])
+            }))
+            .collect();
+
+        Ok(PageView {
+            title: match self.epic_id {
+                Some(epic_id) => format!("BOARD: EPIC {}", epic_id),
+                None => "BOARD".to_owned(),
+            },
+            details: vec![columns.iter().map(|(status, cards)| format!("{}: {}", status, cards.len())).join(" | ")],
+            table: TableView { headers: vec!["status", "id", "name"], widths: vec![14, 8, 40], rows, open: Some("move story to the next column") },
+            keys: vec![("p", "previous"), ("z", "undo"), ("y", "redo")],
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -220,39 +434,104 @@ impl Page for StoryDetail {
 mod tests {
     use super::*;
     use crate::{db::test_utils::MockDB};
-    use crate::models::{Epic, Story};
+    use crate::models::{Comment, Epic, Status, Story};
 
     mod home_page {
         use super::*;
 
+        #[test]
+        fn view_should_list_epics() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+
+            let view = HomePage { db, label: None, list: ListState::default() }.view().unwrap();
+
+            assert_eq!(view.table.rows, vec![TableRow::new(Some(epic_id.to_string()), vec![epic_id.to_string(), "epic".to_owned(), "OPEN".to_owned(), "0/0".to_owned(), "".to_owned()])]);
+            assert!(view.keys.contains(&("c", "create epic")));
+        }
+
+        #[test]
+        fn view_should_filter_epics_by_label() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let backend_epic_id = db.create_epic(Epic::new("backend".to_owned(), "".to_owned())).unwrap();
+            let bug_epic_id = db.create_epic(Epic::new("bugs".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), bug_epic_id).unwrap();
+            db.add_epic_label(backend_epic_id, "backend").unwrap();
+            db.add_story_label(story_id, "bug").unwrap();
+
+            let page = HomePage { db: Rc::clone(&db), label: Some("bug".to_owned()), list: ListState::default() };
+            let view = page.view().unwrap();
+
+            assert_eq!(view.details, vec!["label: bug".to_owned()]);
+            assert_eq!(view.table.rows.iter().map(|row| row.input.clone()).collect::<Vec<_>>(), vec![Some(bug_epic_id.to_string())]);
+            assert!(page.draw_page().is_ok());
+
+            //The line based loop prints the same view, with the filter above the column header
+            let text = view.to_text();
+            assert!(text.find("label: bug").unwrap() < text.find("id ").unwrap());
+
+            let view = HomePage { db, label: None, list: ListState::default() }.view().unwrap();
+            assert_eq!(view.table.rows.len(), 2);
+            assert_eq!(view.table.rows[0].cells[4], "backend".to_owned());
+        }
+
+        #[test]
+        fn view_should_page_and_sort_epics() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            for index in 0..25 {
+                db.create_epic(Epic::new(format!("epic {:02}", 25 - index), "".to_owned())).unwrap();
+            }
+
+            let page = HomePage { db, label: None, list: ListState::default() };
+            let view = page.view().unwrap();
+            assert_eq!(view.table.rows.len(), 20);
+            assert_eq!(view.details, vec!["page 1/2 | sort: id".to_owned()]);
+
+            page.handle_input("n").unwrap();
+            let view = page.view().unwrap();
+            assert_eq!(view.table.rows.len(), 5);
+            assert_eq!(view.table.rows[0].input, Some("21".to_owned()));
+            assert!(page.draw_page().is_ok());
+
+            page.handle_input("b").unwrap();
+            assert_eq!(page.view().unwrap().table.rows.len(), 20);
+
+            // Sorting starts over at the first page
+            page.handle_input("o").unwrap();
+            let view = page.view().unwrap();
+            assert_eq!(view.table.rows[0].cells[1], "epic 01".to_owned());
+            assert_eq!(view.details, vec!["page 1/2 | sort: name".to_owned()]);
+        }
+
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
-            let page = HomePage { db };
-            assert_eq!(page.draw_page().is_ok(), true);
+            let page = HomePage { db, label: None, list: ListState::default() };
+            assert!(page.draw_page().is_ok());
         }
         
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
-            let page = HomePage { db };
-            assert_eq!(page.handle_input("").is_ok(), true);
+            let page = HomePage { db, label: None, list: ListState::default() };
+            assert!(page.handle_input("").is_ok());
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic = Epic::new("".to_owned(), "".to_owned());
 
             let epic_id = db.create_epic(epic).unwrap();
 
-            let page = HomePage { db };
+            let page = HomePage { db, label: None, list: ListState::default() };
 
             let q = "q";
             let c = "c";
+            let s = "s";
             let valid_epic_id = epic_id.to_string();
             let invalid_epic_id = "999";
             let junk_input = "j983f2j";
@@ -261,6 +540,15 @@ mod tests {
 
             assert_eq!(page.handle_input(q).unwrap(), Some(Action::Exit));
             assert_eq!(page.handle_input(c).unwrap(), Some(Action::CreateEpic));
+            assert_eq!(page.handle_input(s).unwrap(), Some(Action::Search));
+            assert_eq!(page.handle_input("v").unwrap(), Some(Action::NavigateToBoard { epic_id: None }));
+            assert_eq!(page.handle_input("l").unwrap(), Some(Action::FilterByLabel));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
+            assert_eq!(page.handle_input("n").unwrap(), None);
+            assert_eq!(page.handle_input("b").unwrap(), None);
+            assert_eq!(page.handle_input("o").unwrap(), None);
+            assert_eq!(page.list.sort(), SortOrder::Name);
             assert_eq!(page.handle_input(&valid_epic_id).unwrap(), Some(Action::NavigateToEpicDetail { epic_id: 1 }));
             assert_eq!(page.handle_input(invalid_epic_id).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
@@ -272,43 +560,90 @@ mod tests {
     mod epic_detail_page {
         use super::*;
 
+        #[test]
+        fn view_should_list_stories() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+
+            let view = EpicDetail { epic_id, db: Rc::clone(&db), list: ListState::default() }.view().unwrap();
+
+            assert_eq!(view.title, format!("EPIC {}: epic", epic_id));
+            assert_eq!(view.table.rows.len(), 1);
+            assert_eq!(view.table.rows[0].input, Some(story_id.to_string()));
+            assert!(view.details.contains(&"progress: 0/1 resolved | labels: -".to_owned()));
+            assert!(EpicDetail { epic_id: 999, db, list: ListState::default() }.view().is_err());
+        }
+
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
 
-            let page = EpicDetail { epic_id, db };
-            assert_eq!(page.draw_page().is_ok(), true);
+            let page = EpicDetail { epic_id, db, list: ListState::default() };
+            assert!(page.draw_page().is_ok());
         }
 
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
 
-            let page = EpicDetail { epic_id, db };
-            assert_eq!(page.handle_input("").is_ok(), true);
+            let page = EpicDetail { epic_id, db, list: ListState::default() };
+            assert!(page.handle_input("").is_ok());
+        }
+
+        #[test]
+        fn view_should_page_and_sort_stories() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_ids = (0..22).map(|_| db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap()).collect::<Vec<u32>>();
+            db.update_story_status(story_ids[21], Status::InProgress).unwrap();
+
+            let page = EpicDetail { epic_id, db, list: ListState::default() };
+            assert_eq!(page.view().unwrap().table.rows.len(), 20);
+
+            page.handle_input("n").unwrap();
+            page.handle_input("n").unwrap();
+            let view = page.view().unwrap();
+            assert_eq!(view.table.rows.len(), 2);
+            assert!(view.details.contains(&"page 2/2 | sort: id".to_owned()));
+            assert!(page.draw_page().is_ok());
+
+            page.handle_input("b").unwrap();
+            assert!(page.view().unwrap().details.contains(&"page 1/2 | sort: id".to_owned()));
+
+            // By status the story in progress comes after all open ones
+            page.handle_input("o").unwrap();
+            page.handle_input("o").unwrap();
+            page.handle_input("n").unwrap();
+            let view = page.view().unwrap();
+            assert_eq!(view.table.rows.last().unwrap().input, Some(story_ids[21].to_string()));
+            assert!(view.details.contains(&"page 2/2 | sort: status".to_owned()));
         }
 
         #[test]
         fn draw_page_should_throw_error_for_invalid_epic_id() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
-            let page = EpicDetail { epic_id: 999, db };
-            assert_eq!(page.draw_page().is_err(), true);
+            let page = EpicDetail { epic_id: 999, db, list: ListState::default() };
+            assert!(page.draw_page().is_err());
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
 
-            let page = EpicDetail { epic_id, db };
+            let page = EpicDetail { epic_id, db, list: ListState::default() };
 
             let p = "p";
+            let e = "e";
             let u = "u";
+            let a = "a";
+            let r = "r";
             let d = "d";
             let c = "c";
             let invalid_story_id = "999";
@@ -317,9 +652,23 @@ mod tests {
             let input_with_trailing_white_spaces = "p\n";
 
             assert_eq!(page.handle_input(p).unwrap(), Some(Action::NavigateToPreviousPage));
+            assert_eq!(page.handle_input(e).unwrap(), Some(Action::UpdateEpic { epic_id: 1 }));
             assert_eq!(page.handle_input(u).unwrap(), Some(Action::UpdateEpicStatus { epic_id: 1 }));
+            assert_eq!(page.handle_input(a).unwrap(), Some(Action::UpdateEpicAssignee { epic_id: 1 }));
+            assert_eq!(page.handle_input(r).unwrap(), Some(Action::UpdateEpicPriority { epic_id: 1 }));
             assert_eq!(page.handle_input(d).unwrap(), Some(Action::DeleteEpic { epic_id: 1 }));
             assert_eq!(page.handle_input(c).unwrap(), Some(Action::CreateStory { epic_id: 1 }));
+            assert_eq!(page.handle_input("v").unwrap(), Some(Action::NavigateToBoard { epic_id: Some(1) }));
+            assert_eq!(page.handle_input("l").unwrap(), Some(Action::AddEpicLabel { epic_id: 1 }));
+            assert_eq!(page.handle_input("L").unwrap(), Some(Action::RemoveEpicLabel { epic_id: 1 }));
+            assert_eq!(page.handle_input("k").unwrap(), Some(Action::AddComment { item_id: 1 }));
+            assert_eq!(page.handle_input("x").unwrap(), Some(Action::DeleteComment { item_id: 1 }));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
+            assert_eq!(page.handle_input("n").unwrap(), None);
+            assert_eq!(page.handle_input("b").unwrap(), None);
+            assert_eq!(page.handle_input("o").unwrap(), None);
+            assert_eq!(page.list.sort(), SortOrder::Name);
             assert_eq!(page.handle_input(&story_id.to_string()).unwrap(), Some(Action::NavigateToStoryDetail { epic_id: 1, story_id: 2 }));
             assert_eq!(page.handle_input(invalid_story_id).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
@@ -331,42 +680,89 @@ mod tests {
     mod story_detail_page {
         use super::*;
 
+        #[test]
+        fn view_should_list_history() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+            db.update_story_status(story_id, Status::Closed).unwrap();
+
+            let view = StoryDetail { epic_id, story_id, db }.view().unwrap();
+
+            // Newest first and not selectable
+            assert_eq!(view.table.rows.len(), 2);
+            assert_eq!(view.table.rows[0].cells[2], "status: OPEN -> CLOSED");
+            assert!(view.table.rows.iter().all(|row| row.input.is_none()));
+        }
+
+        #[test]
+        fn view_should_show_blockers() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let blocker_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+            let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+            db.add_blocker(story_id, blocker_id).unwrap();
+
+            let view = StoryDetail { epic_id, story_id, db: Rc::clone(&db) }.view().unwrap();
+            assert!(view.details.contains(&format!("blocked by: {} (OPEN) | blocks: -", blocker_id)));
+
+            let page = StoryDetail { epic_id, story_id: blocker_id, db };
+            assert!(page.view().unwrap().details.contains(&format!("blocked by: - | blocks: {} (OPEN)", story_id)));
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn view_should_list_comment_threads() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+            let comment_id = db.add_comment(Comment::new(story_id, "alice".to_owned(), "first".to_owned())).unwrap();
+            db.add_comment(Comment { reply_to: Some(comment_id), ..Comment::new(story_id, "bob".to_owned(), "reply".to_owned()) }).unwrap();
+
+            let page = StoryDetail { epic_id, story_id, db };
+            let view = page.view().unwrap();
+
+            assert!(view.details.iter().any(|line| line.starts_with(&format!("[{}] alice (", comment_id)) && line.ends_with("): first")));
+            assert!(view.details.iter().any(|line| line.starts_with(&format!("  [{}] bob (", comment_id + 1)) && line.ends_with("): reply")));
+            assert!(page.draw_page().is_ok());
+        }
+
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
 
             let page = StoryDetail { epic_id, story_id, db };
-            assert_eq!(page.draw_page().is_ok(), true);
+            assert!(page.draw_page().is_ok());
         }
 
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
 
             let page = StoryDetail { epic_id, story_id, db };
-            assert_eq!(page.handle_input("").is_ok(), true);
+            assert!(page.handle_input("").is_ok());
         }
 
         #[test]
         fn draw_page_should_throw_error_for_invalid_story_id() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let _ = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
 
             let page = StoryDetail { epic_id, story_id: 999, db };
-            assert_eq!(page.draw_page().is_err(), true);
+            assert!(page.draw_page().is_err());
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
@@ -374,7 +770,12 @@ mod tests {
             let page = StoryDetail { epic_id, story_id, db };
 
             let p = "p";
+            let e = "e";
             let u = "u";
+            let a = "a";
+            let r = "r";
+            let s = "s";
+            let m = "m";
             let d = "d";
             let some_number = "1";
             let junk_input = "j983f2j";
@@ -382,7 +783,20 @@ mod tests {
             let input_with_trailing_white_spaces = "p\n";
 
             assert_eq!(page.handle_input(p).unwrap(), Some(Action::NavigateToPreviousPage));
+            assert_eq!(page.handle_input(e).unwrap(), Some(Action::UpdateStory { story_id }));
             assert_eq!(page.handle_input(u).unwrap(), Some(Action::UpdateStoryStatus { story_id }));
+            assert_eq!(page.handle_input(a).unwrap(), Some(Action::UpdateStoryAssignee { story_id }));
+            assert_eq!(page.handle_input(r).unwrap(), Some(Action::UpdateStoryPriority { story_id }));
+            assert_eq!(page.handle_input(s).unwrap(), Some(Action::UpdateStoryPoints { story_id }));
+            assert_eq!(page.handle_input(m).unwrap(), Some(Action::MoveStory { epic_id, story_id }));
+            assert_eq!(page.handle_input("l").unwrap(), Some(Action::AddStoryLabel { story_id }));
+            assert_eq!(page.handle_input("L").unwrap(), Some(Action::RemoveStoryLabel { story_id }));
+            assert_eq!(page.handle_input("b").unwrap(), Some(Action::AddBlocker { story_id }));
+            assert_eq!(page.handle_input("B").unwrap(), Some(Action::RemoveBlocker { story_id }));
+            assert_eq!(page.handle_input("k").unwrap(), Some(Action::AddComment { item_id: story_id }));
+            assert_eq!(page.handle_input("x").unwrap(), Some(Action::DeleteComment { item_id: story_id }));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
             assert_eq!(page.handle_input(d).unwrap(), Some(Action::DeleteStory { epic_id, story_id }));
             assert_eq!(page.handle_input(some_number).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
@@ -390,4 +804,112 @@ mod tests {
             assert_eq!(page.handle_input(input_with_trailing_white_spaces).unwrap(), None);
         } 
     }
-}
\ No newline at end of file
+
+    mod search_page {
+        use super::*;
+
+        #[test]
+        fn view_should_list_hits() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("login".to_owned(), "".to_owned())).unwrap();
+            db.create_story(Story::new("billing".to_owned(), "".to_owned()), epic_id).unwrap();
+
+            let view = SearchPage { query: "login".to_owned(), db }.view().unwrap();
+
+            assert_eq!(view.table.rows, vec![TableRow::new(Some(epic_id.to_string()), vec![epic_id.to_string(), "epic".to_owned(), "login".to_owned(), "OPEN".to_owned()])]);
+        }
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+            db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+
+            let page = SearchPage { query: "".to_owned(), db };
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn draw_page_should_throw_error_for_invalid_query() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let page = SearchPage { query: "status:unknown".to_owned(), db };
+            assert!(page.draw_page().is_err());
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db.create_epic(Epic::new("login".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("login form".to_owned(), "".to_owned()), epic_id).unwrap();
+            let other_story_id = db.create_story(Story::new("billing".to_owned(), "".to_owned()), epic_id).unwrap();
+
+            let page = SearchPage { query: "login".to_owned(), db };
+
+            let p = "p";
+            let junk_input = "j983f2j";
+            let input_with_trailing_white_spaces = "p\n";
+
+            assert_eq!(page.handle_input(p).unwrap(), Some(Action::NavigateToPreviousPage));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
+            assert_eq!(page.handle_input(&epic_id.to_string()).unwrap(), Some(Action::NavigateToEpicDetail { epic_id }));
+            assert_eq!(page.handle_input(&story_id.to_string()).unwrap(), Some(Action::NavigateToStoryDetail { epic_id, story_id }));
+            assert_eq!(page.handle_input(&other_story_id.to_string()).unwrap(), None);
+            assert_eq!(page.handle_input("999").unwrap(), None);
+            assert_eq!(page.handle_input(junk_input).unwrap(), None);
+            assert_eq!(page.handle_input(input_with_trailing_white_spaces).unwrap(), None);
+        }
+    }
+
+    mod board_page {
+        use super::*;
+
+        fn sample_db() -> (Rc<JiraDatabase>, u32, u32) {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+            let other_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let other_story_id = db.create_story(Story::new("other story".to_owned(), "".to_owned()), other_epic_id).unwrap();
+            db.update_story_status(other_story_id, Status::Resolved).unwrap();
+
+            (db, epic_id, story_id)
+        }
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let (db, epic_id, _) = sample_db();
+
+            assert!(BoardPage { epic_id: None, db: Rc::clone(&db) }.draw_page().is_ok());
+            assert!(BoardPage { epic_id: Some(epic_id), db: Rc::clone(&db) }.draw_page().is_ok());
+            assert!(BoardPage { epic_id: Some(999), db }.draw_page().is_err());
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let (db, epic_id, story_id) = sample_db();
+
+            let page = BoardPage { epic_id: Some(epic_id), db };
+
+            assert_eq!(page.handle_input("p").unwrap(), Some(Action::NavigateToPreviousPage));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
+            assert_eq!(page.handle_input(&story_id.to_string()).unwrap(), Some(Action::AdvanceStoryStatus { story_id }));
+            assert_eq!(page.handle_input("4").unwrap(), None);
+            assert_eq!(page.handle_input("999").unwrap(), None);
+            assert_eq!(page.handle_input("j983f2j").unwrap(), None);
+        }
+
+        #[test]
+        fn view_should_group_stories_by_status() {
+            let (db, _, story_id) = sample_db();
+
+            let view = BoardPage { epic_id: None, db }.view().unwrap();
+
+            assert_eq!(view.details, vec!["OPEN: 1 | IN PROGRESS: 0 | RESOLVED: 1 | CLOSED: 0".to_owned()]);
+            assert_eq!(view.table.rows.len(), 2);
+            assert_eq!(view.table.rows[0], TableRow::new(Some(story_id.to_string()), vec!["OPEN".to_owned(), story_id.to_string(), "story".to_owned()]));
+        }
+    }
+}
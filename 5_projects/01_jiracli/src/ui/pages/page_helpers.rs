@@ -1,10 +1,14 @@
+use chrono::{DateTime, Local, Utc};
 use ellipse::Ellipse;
 
+use crate::db::{HistoryEvent, Progress};
+use crate::models::{Comment, DBState};
+
 pub fn get_column_string(text: &str, width: usize) -> String {    
     let text_len = text.len();
 
     if text_len == width {
-        return String::from(text);
+        String::from(text)
     }
     else if text_len > width{
         match width{
@@ -16,10 +20,64 @@ pub fn get_column_string(text: &str, width: usize) -> String {
         }
     }
     else{
-        return String::from(format!("{:width$}", text));
+        format!("{:width$}", text)
     }   
 }
 
+pub fn get_timestamp_string(timestamp: &Option<DateTime<Utc>>) -> String {
+    match timestamp {
+        Some(timestamp) => timestamp.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+        None => String::from("-"),
+    }
+}
+
+pub fn get_optional_string<T: ToString>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => String::from("-"),
+    }
+}
+
+pub fn get_labels_string(labels: &[String]) -> String {
+    if labels.is_empty() { String::from("-") } else { labels.join(", ") }
+}
+
+// Story ids with their status, e.g. "4 (OPEN), 7 (RESOLVED)"
+pub fn get_story_links_string(db_state: &DBState, story_ids: &[u32]) -> String {
+    if story_ids.is_empty() {
+        return String::from("-");
+    }
+
+    story_ids.iter()
+        .map(|story_id| match db_state.stories.get(story_id) {
+            Some(story) => format!("{} ({})", story_id, story.status),
+            None => format!("{} (missing)", story_id),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// Short form for table columns, e.g. "3/5 (7 pts left)"
+pub fn get_progress_string(progress: &Progress) -> String {
+    match progress.points_total {
+        0 => format!("{}/{}", progress.done, progress.total),
+        _ => format!("{}/{} ({} pts left)", progress.done, progress.total, progress.points_remaining()),
+    }
+}
+
+// Replies are indented below the comment they answer
+pub fn get_comment_row(depth: usize, comment_id: u32, comment: &Comment) -> String {
+    format!("{}[{}] {} ({}): {}", "  ".repeat(depth), comment_id, comment.author, get_timestamp_string(&Some(comment.created_at)), comment.body)
+}
+
+pub fn get_history_row(event: &HistoryEvent) -> String {
+    let when = get_column_string(&get_timestamp_string(&Some(event.timestamp)), 17);
+    let who = get_column_string(&event.actor, 12);
+    let change = get_column_string(&event.kind.to_string(), 40);
+
+    format!("{}| {}| {}", when, who, change)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,4 +116,56 @@ mod tests {
         assert_eq!(get_column_string(text3, width), "testme".to_owned());
         assert_eq!(get_column_string(text4, width), "tes...".to_owned());
     } 
-}
\ No newline at end of file
+
+    #[test]
+    fn test_get_optional_strings() {
+        assert_eq!(get_timestamp_string(&None), "-".to_owned());
+        assert_eq!(get_timestamp_string(&Some(Utc::now())).len(), "2022-01-01 00:00".len());
+
+        assert_eq!(get_optional_string::<String>(&None), "-".to_owned());
+        assert_eq!(get_optional_string(&Some(5)), "5".to_owned());
+        assert_eq!(get_optional_string(&Some("alice")), "alice".to_owned());
+
+        assert_eq!(get_labels_string(&[]), "-".to_owned());
+        assert_eq!(get_labels_string(&["backend".to_owned(), "bug".to_owned()]), "backend, bug".to_owned());
+    }
+
+    #[test]
+    fn test_get_progress_string() {
+        assert_eq!(get_progress_string(&Progress::default()), "0/0");
+        assert_eq!(get_progress_string(&Progress { done: 3, total: 5, points_done: 1, points_total: 8 }), "3/5 (7 pts left)");
+    }
+
+    #[test]
+    fn test_get_story_links_string() {
+        use crate::models::{Epic, Status, Story};
+
+        let mut db_state = DBState::default();
+        let epic_id = db_state.create_epic(Epic::new("".to_owned(), "".to_owned()));
+        let story_id = db_state.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        db_state.update_story_status(story_id, Status::Resolved).unwrap();
+
+        assert_eq!(get_story_links_string(&db_state, &[]), "-");
+        assert_eq!(get_story_links_string(&db_state, &[story_id, 99]), format!("{} (RESOLVED), 99 (missing)", story_id));
+    }
+
+    #[test]
+    fn test_get_comment_row() {
+        let comment = Comment::new(2, "alice".to_owned(), "looks good".to_owned());
+        let when = get_timestamp_string(&Some(comment.created_at));
+
+        assert_eq!(get_comment_row(0, 3, &comment), format!("[3] alice ({}): looks good", when));
+        assert_eq!(get_comment_row(2, 3, &comment), format!("    [3] alice ({}): looks good", when));
+    }
+
+    #[test]
+    fn test_get_history_row() {
+        use crate::db::{EventKind, ItemType};
+
+        let event = HistoryEvent { timestamp: Utc::now(), actor: "alice".to_owned(), item_type: ItemType::Story, item_id: 2, kind: EventKind::Changed { field: "status".to_owned(), from: "OPEN".to_owned(), to: "CLOSED".to_owned() } };
+        let row = get_history_row(&event);
+
+        assert!(row.ends_with("| alice       | status: OPEN -> CLOSED                  "));
+        assert_eq!(row.len(), 17 + 2 + 12 + 2 + 40);
+    }
+}
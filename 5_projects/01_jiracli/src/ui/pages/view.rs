@@ -0,0 +1,109 @@
+use super::page_helpers;
+
+// Key hints of the line based UI wrap after this many characters
+const TEXT_WIDTH: usize = 100;
+
+/// What a page shows, independent of how it is rendered. Used by the full screen terminal UI and the line based loop.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct PageView {
+    pub title: String,
+    pub details: Vec<String>,
+    pub table: TableView,
+    // (key, description) pairs, the key is passed to `Page::handle_input` as is
+    pub keys: Vec<(&'static str, &'static str)>,
+}
+
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct TableView {
+    pub headers: Vec<&'static str>,
+    pub widths: Vec<u16>,
+    pub rows: Vec<TableRow>,
+    // What opening a row does, e.g. "navigate to epic". None when rows can't be opened.
+    pub open: Option<&'static str>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TableRow {
+    // Input passed to `Page::handle_input` when the row is opened, None for rows that can't be opened
+    pub input: Option<String>,
+    pub cells: Vec<String>,
+}
+
+impl PageView {
+    /// Renders the view as text for the line based loop, where a row is opened by typing its id.
+    pub fn to_text(&self) -> String {
+        let widths = self.table.widths.iter().map(|width| *width as usize).collect::<Vec<usize>>();
+        let table_width = (widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1)).max(self.title.len() + 2);
+        let table_line = |cells: Vec<&str>| cells.iter().zip(&widths).map(|(cell, width)| page_helpers::get_column_string(cell, *width)).collect::<Vec<String>>().join("| ");
+
+        let mut lines = vec![format!("{:-^table_width$}", format!(" {} ", self.title))];
+        lines.extend(self.details.iter().cloned());
+        lines.push(String::new());
+        lines.push(table_line(self.table.headers.clone()));
+        lines.extend(self.table.rows.iter().map(|row| table_line(row.cells.iter().map(String::as_str).collect())));
+        lines.push(String::new());
+
+        //Wrap the key hints into lines of at most TEXT_WIDTH characters
+        let hints = self.keys.iter().map(|(key, description)| format!("[{}] {}", key, description))
+            .chain(self.table.open.map(|open| format!("[:id:] {}", open)));
+        let mut hint_line = String::new();
+        for hint in hints {
+            if !hint_line.is_empty() && hint_line.len() + 3 + hint.len() > TEXT_WIDTH {
+                lines.push(std::mem::take(&mut hint_line));
+            }
+            if !hint_line.is_empty() {
+                hint_line.push_str(" | ");
+            }
+            hint_line.push_str(&hint);
+        }
+        lines.push(hint_line);
+
+        lines.join("\n")
+    }
+}
+
+impl TableRow {
+    pub fn new(input: Option<String>, cells: Vec<String>) -> Self {
+        TableRow { input, cells }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_text_should_render_details_table_and_keys() {
+        let view = PageView {
+            title: "EPICS".to_owned(),
+            details: vec!["label: bug".to_owned()],
+            table: TableView {
+                headers: vec!["id", "name"],
+                widths: vec![4, 10],
+                rows: vec![TableRow::new(Some("1".to_owned()), vec!["1".to_owned(), "a very long name".to_owned()])],
+                open: Some("navigate to epic"),
+            },
+            keys: vec![("q", "quit"), ("c", "create epic")],
+        };
+
+        assert_eq!(view.to_text(), [
+            "---- EPICS -----",
+            "label: bug",
+            "",
+            "id  | name      ",
+            "1   | a very ...",
+            "",
+            "[q] quit | [c] create epic | [:id:] navigate to epic",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn to_text_should_wrap_key_hints() {
+        let view = PageView { keys: vec![("q", "quit"); 20], ..Default::default() };
+        let text = view.to_text();
+        let hint_lines = text.lines().skip_while(|line| !line.starts_with('[')).collect::<Vec<&str>>();
+
+        assert_eq!(hint_lines.len(), 3);
+        assert!(hint_lines.iter().all(|line| line.len() <= TEXT_WIDTH));
+    }
+}
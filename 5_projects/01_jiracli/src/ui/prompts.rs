@@ -1,11 +1,32 @@
-use crate::{models::{Epic, Story, Status}, io_utils::get_user_input};
+use crate::{models::{Epic, Story, Status, Priority}, io_utils::get_user_input};
+
+// (name, description) as entered by the user
+pub type NameAndDescription = (String, String);
+// (body, id of the comment to reply to) as entered by the user
+pub type CommentAndReplyTo = (String, Option<u32>);
+// Offered the valid next states of the workflow, returns the chosen one
+pub type StatusPrompt = dyn Fn(&[Status]) -> Option<Status>;
 
 pub struct Prompts {
     pub create_epic: Box<dyn Fn() -> Epic>,
     pub create_story: Box<dyn Fn() -> Story>,
+    pub update_epic: Box<dyn Fn(&Epic) -> NameAndDescription>,
+    pub update_story: Box<dyn Fn(&Story) -> NameAndDescription>,
+    pub move_story: Box<dyn Fn() -> Option<u32>>,
     pub delete_epic: Box<dyn Fn() -> bool>,
     pub delete_story: Box<dyn Fn() -> bool>,
-    pub update_status: Box<dyn Fn() -> Option<Status>>
+    pub update_status: Box<StatusPrompt>,
+    pub update_assignee: Box<dyn Fn() -> Option<String>>,
+    pub update_priority: Box<dyn Fn() -> Option<Priority>>,
+    pub update_story_points: Box<dyn Fn() -> Option<u32>>,
+    pub add_comment: Box<dyn Fn() -> Option<CommentAndReplyTo>>,
+    pub delete_comment: Box<dyn Fn() -> Option<u32>>,
+    pub add_label: Box<dyn Fn() -> Option<String>>,
+    pub remove_label: Box<dyn Fn() -> Option<String>>,
+    pub filter_by_label: Box<dyn Fn() -> Option<String>>,
+    pub add_blocker: Box<dyn Fn() -> Option<u32>>,
+    pub remove_blocker: Box<dyn Fn() -> Option<u32>>,
+    pub search: Box<dyn Fn() -> String>
 }
 
 impl Prompts {
@@ -13,9 +34,23 @@ impl Prompts {
         Self { 
             create_epic: Box::new(create_epic_prompt),
             create_story: Box::new(create_story_prompt),
+            update_epic: Box::new(update_epic_prompt),
+            update_story: Box::new(update_story_prompt),
+            move_story: Box::new(move_story_prompt),
             delete_epic: Box::new(delete_epic_prompt),
             delete_story: Box::new(delete_story_prompt),
-            update_status: Box::new(update_status_prompt)
+            update_status: Box::new(update_status_prompt),
+            update_assignee: Box::new(update_assignee_prompt),
+            update_priority: Box::new(update_priority_prompt),
+            update_story_points: Box::new(update_story_points_prompt),
+            add_comment: Box::new(add_comment_prompt),
+            delete_comment: Box::new(delete_comment_prompt),
+            add_label: Box::new(add_label_prompt),
+            remove_label: Box::new(remove_label_prompt),
+            filter_by_label: Box::new(filter_by_label_prompt),
+            add_blocker: Box::new(add_blocker_prompt),
+            remove_blocker: Box::new(remove_blocker_prompt),
+            search: Box::new(search_prompt)
         }
     }
 }
@@ -40,6 +75,39 @@ fn create_story_prompt() -> Story {
     Story::new(sname, sdesc)
 }
 
+fn update_epic_prompt(epic: &Epic) -> NameAndDescription {
+    println!("----------------------------");
+    println!("Press enter to keep the current value.");
+    let ename = get_user_input_or_default("Epic Name", &epic.name);
+    let edesc = get_user_input_or_default("Epic Description", &epic.description);
+
+    (ename, edesc)
+}
+
+fn update_story_prompt(story: &Story) -> NameAndDescription {
+    println!("----------------------------");
+    println!("Press enter to keep the current value.");
+    let sname = get_user_input_or_default("Story Name", &story.name);
+    let sdesc = get_user_input_or_default("Story Description", &story.description);
+
+    (sname, sdesc)
+}
+
+fn get_user_input_or_default(label: &str, current: &str) -> String {
+    println!("{} [{}]: ", label, current);
+    let reply = get_user_input();
+
+    if reply.is_empty() { current.to_owned() } else { reply }
+}
+
+fn move_story_prompt() -> Option<u32> {
+    println!("----------------------------");
+    println!("Move story to Epic Id:");
+    let reply = get_user_input();
+
+    reply.parse::<u32>().ok()
+}
+
 fn delete_epic_prompt() -> bool {
     println!("----------------------------");
     println!("Are you sure you want to delete this epic? All stories in this epic will also be deleted [Y/n]:");
@@ -56,21 +124,112 @@ fn delete_story_prompt() -> bool {
     reply == "Y"
 }
 
-fn update_status_prompt() -> Option<Status> {
+fn update_status_prompt(statuses: &[Status]) -> Option<Status> {
+    let options = statuses.iter().enumerate().map(|(index, status)| format!("{} - {}", index + 1, status)).collect::<Vec<String>>();
+
     println!("----------------------------");
-    println!("New Status (1 - OPEN, 2 - IN-PROGRESS, 3 - RESOLVED, 4 - CLOSED):");
+    println!("New Status ({}):", options.join(", "));
     let reply = get_user_input();
 
-    let result_option = reply.parse::<u8>();
+    let result_option = reply.parse::<usize>();
 
     match result_option{
-        Ok(reply_option)=> match reply_option {
-            1 => Some(Status::Open),
-            2 => Some(Status::InProgress),
-            3 => Some(Status::Resolved),
-            4 => Some(Status::Closed),
-            _ => None,
-        },
-        Err(e) => None,
+        Ok(reply_option) if reply_option >= 1 => statuses.get(reply_option - 1).cloned(),
+        _ => None,
     }
+}
+
+fn update_assignee_prompt() -> Option<String> {
+    println!("----------------------------");
+    println!("New Assignee (leave empty to unassign):");
+    let reply = get_user_input();
+
+    if reply.is_empty() { None } else { Some(reply) }
+}
+
+fn update_priority_prompt() -> Option<Priority> {
+    println!("----------------------------");
+    println!("New Priority (1 - LOW, 2 - MEDIUM, 3 - HIGH, 4 - CRITICAL):");
+    let reply = get_user_input();
+
+    match reply.parse::<u8>() {
+        Ok(1) => Some(Priority::Low),
+        Ok(2) => Some(Priority::Medium),
+        Ok(3) => Some(Priority::High),
+        Ok(4) => Some(Priority::Critical),
+        _ => None,
+    }
+}
+
+fn update_story_points_prompt() -> Option<u32> {
+    println!("----------------------------");
+    println!("New Story Points:");
+    let reply = get_user_input();
+
+    reply.parse::<u32>().ok()
+}
+
+fn add_comment_prompt() -> Option<CommentAndReplyTo> {
+    println!("----------------------------");
+    println!("Reply to Comment Id (leave empty to start a new thread):");
+    let reply_to = get_user_input().parse::<u32>().ok();
+    println!("Comment:");
+    let body = get_user_input();
+
+    if body.is_empty() { None } else { Some((body, reply_to)) }
+}
+
+fn delete_comment_prompt() -> Option<u32> {
+    println!("----------------------------");
+    println!("Delete Comment Id (replies are deleted too):");
+    let reply = get_user_input();
+
+    reply.parse::<u32>().ok()
+}
+
+fn add_label_prompt() -> Option<String> {
+    println!("----------------------------");
+    println!("Add Label (e.g. backend, bug, tech-debt):");
+    let reply = get_user_input();
+
+    if reply.is_empty() { None } else { Some(reply) }
+}
+
+fn remove_label_prompt() -> Option<String> {
+    println!("----------------------------");
+    println!("Remove Label:");
+    let reply = get_user_input();
+
+    if reply.is_empty() { None } else { Some(reply) }
+}
+
+fn filter_by_label_prompt() -> Option<String> {
+    println!("----------------------------");
+    println!("Filter by Label (leave empty to show all epics):");
+    let reply = get_user_input();
+
+    if reply.is_empty() { None } else { Some(reply) }
+}
+
+fn add_blocker_prompt() -> Option<u32> {
+    println!("----------------------------");
+    println!("Blocked by Story Id:");
+    let reply = get_user_input();
+
+    reply.parse::<u32>().ok()
+}
+
+fn remove_blocker_prompt() -> Option<u32> {
+    println!("----------------------------");
+    println!("No longer blocked by Story Id:");
+    let reply = get_user_input();
+
+    reply.parse::<u32>().ok()
+}
+
+fn search_prompt() -> String {
+    println!("----------------------------");
+    println!("Search (text and filters e.g. login status:inprogress epic:2 type:story assignee:alice priority:high label:bug):");
+
+    get_user_input()
 }
\ No newline at end of file
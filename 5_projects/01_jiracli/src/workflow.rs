@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::models::Status;
+
+/// The statuses a board uses and which status changes are allowed, e.g. in ./data/workflow.toml:
+///
+/// ```toml
+/// statuses = ["Open", "InProgress", "Closed"]
+///
+/// # Optional, refuse to close an epic while any of its stories is not resolved or closed.
+/// # Has to come before [transitions], or it is read as a transition.
+/// close_epic_requires_resolved_stories = true
+///
+/// [transitions]
+/// Open = ["InProgress"]
+/// InProgress = ["Open", "Closed"]
+/// Closed = ["Open"]
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Workflow {
+    // In board order. New epics and stories start as Open, so it must be listed.
+    statuses: Vec<Status>,
+    // Statuses without an entry can't be changed
+    transitions: HashMap<Status, Vec<Status>>,
+    #[serde(default)]
+    pub close_epic_requires_resolved_stories: bool,
+}
+
+impl Default for Workflow {
+    // Every status, and any status can change to any other
+    fn default() -> Self {
+        let transitions = Status::ALL.iter()
+            .map(|from| (from.clone(), Status::ALL.iter().filter(|to| *to != from).cloned().collect()))
+            .collect();
+
+        Workflow { statuses: Status::ALL.to_vec(), transitions, close_epic_requires_resolved_stories: false }
+    }
+}
+
+impl Workflow {
+    pub fn parse(workflow_toml: &str) -> Result<Self> {
+        let workflow = toml::from_str::<Workflow>(workflow_toml)?;
+        workflow.validate()?;
+
+        Ok(workflow)
+    }
+
+    /// Loads the workflow file, or the default workflow when there is none.
+    pub fn load(file_path: &str) -> Result<Self> {
+        match std::fs::read_to_string(file_path) {
+            Ok(workflow_toml) => Self::parse(&workflow_toml).with_context(|| format!("Invalid workflow file: {}", file_path)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| format!("Unable to read workflow file: {}", file_path)),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !self.statuses.contains(&Status::Open) {
+            return Err(anyhow!("statuses must include Open, the status new epics and stories start with"));
+        }
+
+        for (index, status) in self.statuses.iter().enumerate() {
+            if self.statuses[..index].contains(status) {
+                return Err(anyhow!("status {} is listed more than once", status));
+            }
+        }
+
+        for (from, to) in &self.transitions {
+            if let Some(status) = std::iter::once(from).chain(to).find(|status| !self.statuses.contains(status)) {
+                return Err(anyhow!("transition {} uses status {} which is not in statuses", from, status));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn statuses(&self) -> &[Status] {
+        &self.statuses
+    }
+
+    /// The statuses an item in `from` may change to, in board order.
+    pub fn transitions_from(&self, from: &Status) -> Vec<Status> {
+        let allowed = self.transitions.get(from);
+        self.statuses.iter().filter(|to| allowed.is_some_and(|allowed| allowed.contains(to))).cloned().collect()
+    }
+
+    /// Errors unless `from` may change to `to`. Keeping the same status is always allowed.
+    pub fn check_transition(&self, from: &Status, to: &Status) -> Result<()> {
+        if from == to || self.transitions_from(from).contains(to) {
+            Ok(())
+        }
+        else if !self.statuses.contains(to) {
+            Err(anyhow!("Status {} is not part of the workflow.", to))
+        }
+        else {
+            Err(anyhow!("Status {} can't change to {} in this workflow.", from, to))
+        }
+    }
+
+    /// The next allowed status further along the board, used to advance a card by one column.
+    pub fn next_status(&self, from: &Status) -> Option<Status> {
+        let position = self.statuses.iter().position(|status| status == from)?;
+        self.transitions_from(from).into_iter().find(|to| self.statuses.iter().position(|status| status == to) > Some(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW_TOML: &str = r#"
+        statuses = ["Open", "InProgress", "Closed"]
+
+        [transitions]
+        Open = ["InProgress"]
+        InProgress = ["Closed", "Open"]
+    "#;
+
+    #[test]
+    fn default_should_allow_any_transition() {
+        let workflow = Workflow::default();
+
+        assert_eq!(workflow.statuses(), Status::ALL.as_slice());
+        assert_eq!(workflow.transitions_from(&Status::Open), vec![Status::InProgress, Status::Resolved, Status::Closed]);
+        assert!(workflow.check_transition(&Status::Closed, &Status::Open).is_ok());
+    }
+
+    #[test]
+    fn parse_should_restrict_transitions() {
+        let workflow = Workflow::parse(WORKFLOW_TOML).unwrap();
+
+        assert_eq!(workflow.transitions_from(&Status::InProgress), vec![Status::Open, Status::Closed]);
+        assert!(workflow.transitions_from(&Status::Closed).is_empty());
+
+        assert!(workflow.check_transition(&Status::Open, &Status::InProgress).is_ok());
+        assert!(workflow.check_transition(&Status::Open, &Status::Open).is_ok());
+        assert!(workflow.check_transition(&Status::Open, &Status::Closed).is_err());
+        assert!(workflow.check_transition(&Status::Open, &Status::Resolved).is_err());
+        assert!(workflow.check_transition(&Status::Closed, &Status::Open).is_err());
+    }
+
+    #[test]
+    fn parse_should_read_close_epic_rule() {
+        assert!(!Workflow::parse(WORKFLOW_TOML).unwrap().close_epic_requires_resolved_stories);
+
+        let workflow_toml = format!("close_epic_requires_resolved_stories = true\n{}", WORKFLOW_TOML);
+        assert!(Workflow::parse(&workflow_toml).unwrap().close_epic_requires_resolved_stories);
+    }
+
+    // The ```toml example from the doc comment on Workflow, as written
+    fn documented_example() -> String {
+        include_str!("workflow.rs").lines()
+            .map(str::trim_start)
+            .skip_while(|line| *line != "/// ```toml")
+            .skip(1)
+            .take_while(|line| *line != "/// ```")
+            .map(|line| line.trim_start_matches("///").trim_start())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn parse_should_read_the_documented_example() {
+        let workflow_toml = documented_example();
+        assert!(workflow_toml.contains("[transitions]"));
+
+        let workflow = Workflow::parse(&workflow_toml).unwrap();
+        assert!(workflow.close_epic_requires_resolved_stories);
+        assert_eq!(workflow.transitions_from(&Status::InProgress), vec![Status::Open, Status::Closed]);
+
+        //Placed after the table header the key is taken as a transition, which is an error
+        let misplaced_toml = workflow_toml.replace("close_epic_requires_resolved_stories = true", "") + "\nclose_epic_requires_resolved_stories = true\n";
+        assert!(Workflow::parse(&misplaced_toml).is_err());
+    }
+
+    #[test]
+    fn parse_should_reject_invalid_workflows() {
+        assert!(Workflow::parse("statuses = [\"InProgress\"]\n[transitions]").is_err());
+        assert!(Workflow::parse("statuses = [\"Open\", \"Open\"]\n[transitions]").is_err());
+        assert!(Workflow::parse("statuses = [\"Open\"]\n[transitions]\nOpen = [\"Closed\"]").is_err());
+        assert!(Workflow::parse("statuses = [\"Open\", \"Done\"]\n[transitions]").is_err());
+    }
+
+    #[test]
+    fn next_status_should_move_along_the_board() {
+        let workflow = Workflow::parse(WORKFLOW_TOML).unwrap();
+
+        assert_eq!(workflow.next_status(&Status::Open), Some(Status::InProgress));
+        assert_eq!(workflow.next_status(&Status::InProgress), Some(Status::Closed));
+        assert_eq!(workflow.next_status(&Status::Closed), None);
+        assert_eq!(workflow.next_status(&Status::Resolved), None);
+    }
+
+    #[test]
+    fn load_should_default_when_file_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("workflow.toml").to_str().unwrap().to_owned();
+
+        assert_eq!(Workflow::load(&file_path).unwrap(), Workflow::default());
+
+        std::fs::write(&file_path, WORKFLOW_TOML).unwrap();
+        assert_eq!(Workflow::load(&file_path).unwrap().statuses(), &[Status::Open, Status::InProgress, Status::Closed]);
+
+        std::fs::write(&file_path, "statuses = 1").unwrap();
+        assert!(Workflow::load(&file_path).is_err());
+    }
+}